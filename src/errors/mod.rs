@@ -0,0 +1,348 @@
+// Copyright 2018 Christopher Simpkins
+// Licensed under the MIT license
+
+//! Command line parsing error support
+
+use std::error::Error;
+use std::fmt;
+
+use parsers::Span;
+use spec::{self, REDACTED};
+
+/// The category of a `ParseError`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ErrorKind {
+    /// An option that is not included in the valid options for the command
+    InvalidOption(String),
+    /// A definition option that is not included in the valid definition options for the command
+    InvalidDefinition(String),
+    /// An option that requires a value was used without a value
+    MissingValue(String),
+    /// An option that does not take a value was used with a value
+    UnexpectedValue(String),
+    /// A required option was not used in the command
+    MissingRequired(String),
+    /// An option value that is not included in the valid choices for the option.  The fields are the option, the value, and the valid choices.
+    InvalidChoice(String, String, Vec<String>),
+    /// An option value that failed an option value validator or parser.  The fields are the option, the value, and the failure message.
+    InvalidValue(String, String, String),
+    /// A configuration file that could not be read or parsed.  The fields are the file path and the failure message.
+    InvalidConfig(String, String),
+    /// A command alias that could not be expanded.  The fields are the alias and the failure message.
+    InvalidAlias(String, String),
+    /// An executable name or first positional argument that is not an applet of a multicall executable
+    InvalidApplet(String),
+    /// A multicall executable that was used with its main name and without an applet name
+    MissingApplet,
+    /// A command line string that could not be split into arguments (e.g., with an unterminated quote).  The field is the failure message.
+    InvalidSyntax(String),
+    /// A sensitive option value that could not be prompted for because the terminal echo of the input could not be disabled
+    InsecureInput(String),
+}
+
+/// A command line parsing error with the location of the argument that caused the error
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParseError {
+    /// The category of the error
+    pub kind: ErrorKind,
+    /// The location of the argument, or the part of the argument, that caused the error. `None` if the error is not associated with an argument.
+    pub span: Option<Span>,
+}
+
+impl ParseError {
+    /// Instantiates and returns a new `ParseError` struct
+    pub fn new(kind: ErrorKind, span: Option<Span>) -> Self {
+        ParseError { kind, span }
+    }
+}
+
+// Display trait
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::InvalidOption(option) => write!(f, "invalid option '{}'", option),
+            ErrorKind::InvalidDefinition(option) => {
+                write!(f, "invalid definition option '{}'", option)
+            }
+            ErrorKind::MissingValue(option) => write!(f, "option '{}' requires a value", option),
+            ErrorKind::UnexpectedValue(option) => {
+                write!(f, "option '{}' does not take a value", option)
+            }
+            ErrorKind::MissingRequired(option) => {
+                write!(f, "missing required option '{}'", option)
+            }
+            ErrorKind::InvalidChoice(option, value, choices) => write!(
+                f,
+                "invalid value '{}' for option '{}' (possible values: {})",
+                value,
+                option,
+                choices.join(", ")
+            ),
+            ErrorKind::InvalidValue(option, value, message) => write!(
+                f,
+                "invalid value '{}' for option '{}': {}",
+                value, option, message
+            ),
+            ErrorKind::InvalidConfig(path, message) => {
+                write!(f, "invalid configuration file '{}': {}", path, message)
+            }
+            ErrorKind::InvalidAlias(alias, message) => {
+                write!(f, "invalid alias '{}': {}", alias, message)
+            }
+            ErrorKind::InvalidApplet(applet) => write!(f, "invalid applet '{}'", applet),
+            ErrorKind::MissingApplet => write!(f, "missing applet name"),
+            ErrorKind::InvalidSyntax(message) => write!(f, "invalid command line: {}", message),
+            ErrorKind::InsecureInput(option) => {
+                write!(f, "cannot hide the input of sensitive option '{}'", option)
+            }
+        }
+    }
+}
+
+// Error trait
+impl Error for ParseError {}
+
+/// Returns `String` with the `error` message followed by the command line in `argv` and a caret line that marks the location of the argument that caused the error.
+///
+/// # Remarks
+/// Use `spec::Spec::render_error` or `render_redacted_error` to redact the values of sensitive options in `argv`.  The caret line is omitted when the error does not have a location in `argv`.  For example, an invalid `-o` switch in the command `tool -xvo file` is rendered as:
+///
+/// ```text
+/// error: invalid option '-o'
+///   tool -xvo file
+///           ^
+/// ```
+pub fn render_error(argv: &[String], error: &ParseError) -> String {
+    let mut rendered = format!("error: {}\n  {}", error, argv.join(" "));
+    if let Some(span) = error.span {
+        let arg = argv.get(span.index);
+        // the caret line is omitted for an offset that is out of range or not on a char boundary
+        let parts = arg.and_then(|x| Some((x.get(..span.start)?, x.get(span.start..span.end)?)));
+        if let Some((before, located)) = parts {
+            // offset: two space indent + each preceding argument with the space delimiter
+            let offset: usize = 2
+                + argv[..span.index]
+                    .iter()
+                    .map(|x| x.chars().count() + 1)
+                    .sum::<usize>()
+                + before.chars().count();
+            let width = located.chars().count().max(1);
+            rendered.push('\n');
+            rendered.push_str(&" ".repeat(offset));
+            rendered.push_str(&"^".repeat(width));
+        }
+    }
+
+    rendered
+}
+
+/// Returns `String` with the `error` message followed by the command line in `argv` with the text at each location in `spans` replaced with `REDACTED`, and a caret line that marks the location of the argument that caused the error in the redacted command line.
+///
+/// # Remarks
+/// The location of the error is moved to the same text in the redacted command line.  A location in a redacted value marks the `REDACTED` text.  Use `spec::Spec::render_error` to redact the values of the sensitive options in a specification.
+pub fn render_redacted_error(argv: &[String], spans: &[Span], error: &ParseError) -> String {
+    let redacted = ParseError::new(
+        error.kind.clone(),
+        error.span.map(|x| redacted_span(argv, spans, x)),
+    );
+    render_error(&spec::redact_spans(argv, spans), &redacted)
+}
+
+// Returns the location `span` in `argv` moved to the same text after the text at each location in `spans` is redacted
+fn redacted_span(argv: &[String], spans: &[Span], span: Span) -> Span {
+    let arg = match argv.get(span.index) {
+        Some(x) => x,
+        None => return span,
+    };
+    let mut located = span;
+    // the locations are in the unredacted argument, so the offsets are moved from the last to the first redaction
+    let mut redactions: Vec<&Span> = spans
+        .iter()
+        .filter(|x| x.index == span.index)
+        .filter(|x| arg.get(x.start..x.end).is_some_and(|v| !v.is_empty()))
+        .collect();
+    redactions.sort_by_key(|x| x.start);
+    for x in redactions.iter().rev() {
+        let moved = |offset: usize, inside: usize| {
+            if offset >= x.end {
+                offset - (x.end - x.start) + REDACTED.len()
+            } else if offset > x.start {
+                inside
+            } else {
+                offset
+            }
+        };
+        located.start = moved(located.start, x.start);
+        located.end = moved(located.end, x.start + REDACTED.len());
+    }
+    located
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn function_render_error_short_option_in_mops() {
+        let argv = vec![
+            String::from("tool"),
+            String::from("-xvo"),
+            String::from("file"),
+        ];
+        let error = ParseError::new(
+            ErrorKind::InvalidOption(String::from("-o")),
+            Some(Span {
+                index: 1,
+                start: 3,
+                end: 4,
+            }),
+        );
+        let expected = "error: invalid option '-o'\n  tool -xvo file\n          ^";
+        assert_eq!(render_error(&argv, &error), expected);
+    }
+
+    #[test]
+    fn function_render_error_long_option() {
+        let argv = vec![
+            String::from("tool"),
+            String::from("sub"),
+            String::from("--bogus=def"),
+        ];
+        let error = ParseError::new(
+            ErrorKind::InvalidDefinition(String::from("--bogus")),
+            Some(Span {
+                index: 2,
+                start: 0,
+                end: 7,
+            }),
+        );
+        let expected =
+            "error: invalid definition option '--bogus'\n  tool sub --bogus=def\n           ^^^^^^^";
+        assert_eq!(render_error(&argv, &error), expected);
+    }
+
+    #[test]
+    fn function_render_error_without_span() {
+        let argv = vec![String::from("tool"), String::from("-o")];
+        let error = ParseError::new(ErrorKind::InvalidOption(String::from("-o")), None);
+        assert_eq!(
+            render_error(&argv, &error),
+            "error: invalid option '-o'\n  tool -o"
+        );
+    }
+
+    #[test]
+    fn function_render_error_out_of_bounds_span() {
+        let argv = vec![String::from("tool")];
+        let error = ParseError::new(
+            ErrorKind::InvalidOption(String::from("-o")),
+            Some(Span {
+                index: 4,
+                start: 0,
+                end: 2,
+            }),
+        );
+        assert_eq!(
+            render_error(&argv, &error),
+            "error: invalid option '-o'\n  tool"
+        );
+    }
+
+    #[test]
+    fn function_render_redacted_error() {
+        let argv = vec![
+            String::from("tool"),
+            String::from("--password=hunter2"),
+            String::from("-x"),
+        ];
+        let spans = [Span {
+            index: 1,
+            start: 11,
+            end: 18,
+        }];
+        let error1 = ParseError::new(
+            ErrorKind::InvalidValue(
+                String::from("--password"),
+                String::from("****"),
+                String::from("too short"),
+            ),
+            Some(Span {
+                index: 1,
+                start: 11,
+                end: 18,
+            }),
+        );
+        let error2 = ParseError::new(
+            ErrorKind::UnexpectedValue(String::from("--password")),
+            Some(Span {
+                index: 1,
+                start: 0,
+                end: 18,
+            }),
+        );
+        let error3 = ParseError::new(
+            ErrorKind::InvalidOption(String::from("-x")),
+            Some(Span {
+                index: 2,
+                start: 0,
+                end: 2,
+            }),
+        );
+        assert_eq!(
+            render_redacted_error(&argv, &spans, &error1),
+            "error: invalid value '****' for option '--password': too short\n  tool --password=**** -x\n                  ^^^^"
+        );
+        assert_eq!(
+            render_redacted_error(&argv, &spans, &error2),
+            "error: option '--password' does not take a value\n  tool --password=**** -x\n       ^^^^^^^^^^^^^^^"
+        );
+        assert_eq!(
+            render_redacted_error(&argv, &spans, &error3),
+            "error: invalid option '-x'\n  tool --password=**** -x\n                       ^^"
+        );
+    }
+
+    #[test]
+    fn function_render_error_invalid_span_offsets() {
+        let argv = vec![String::from("tool"), String::from("-ü")];
+        let error1 = ParseError::new(
+            ErrorKind::InvalidOption(String::from("-ü")),
+            Some(Span {
+                index: 1,
+                start: 1,
+                end: 2,
+            }),
+        );
+        let error2 = ParseError::new(
+            ErrorKind::InvalidOption(String::from("-ü")),
+            Some(Span {
+                index: 1,
+                start: 2,
+                end: 9,
+            }),
+        );
+        let error3 = ParseError::new(
+            ErrorKind::InvalidOption(String::from("-ü")),
+            Some(Span {
+                index: 1,
+                start: 2,
+                end: 1,
+            }),
+        );
+        assert_eq!(
+            render_error(&argv, &error1),
+            "error: invalid option '-ü'\n  tool -ü"
+        );
+        assert_eq!(
+            render_error(&argv, &error2),
+            "error: invalid option '-ü'\n  tool -ü"
+        );
+        assert_eq!(
+            render_error(&argv, &error3),
+            "error: invalid option '-ü'\n  tool -ü"
+        );
+    }
+}
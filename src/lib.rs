@@ -4,16 +4,31 @@
 //! `commandlines` is a command line argument parsing library for the development of Rust command line interface (CLI) applications that follow the [POSIX / GNU conventions for command line arguments](https://www.gnu.org/software/libc/manual/html_node/Argument-Syntax.html).
 //!
 //! It is in development and the API is not stable.  Please see the [source repository README.md page](https://github.com/chrissimpkins/commandlines-rust) for updates on the level of library support for the POSIX/GNU command line argument syntax.
+//!
+//! # Cargo features
+//!
+//! - `serde`: `Serialize` and `Deserialize` trait implementations for the `Command` struct and the parsed value, token, and error types (e.g., to log the parsed data of a command as JSON)
+
+#![allow(clippy::new_without_default)]
+#![cfg_attr(test, allow(clippy::bool_assert_comparison))]
 
-#![cfg_attr(feature = "cargo-clippy", allow(new_without_default_derive))]
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 
+pub mod errors;
 pub mod parsers;
+pub mod spec;
 pub mod utilities;
 
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
 
+use errors::{ErrorKind, ParseError};
+
 /// A command line argument object
 ///
 /// The `Command` struct defines fields that hold parsed command line argument data and provides methods that can be used to define the logic of a command line interface application.
@@ -47,7 +62,8 @@ use std::fmt;
 /// The Vector of command line arguments presented to the executable in `std::env::args().collect()` is used to define the `Command` struct fields.
 ///
 /// See the documentation for the `Command` struct methods and fields to learn how to use the parsed data in your command line interface application logic.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
 pub struct Command {
     /// Vector of ordered command line arguments
     pub argv: Vec<String>,
@@ -65,8 +81,22 @@ pub struct Command {
     pub last_arg: Option<String>,
     /// `Option<Vec<String>>` of ordered arguments that follow a double hyphen command line idiom. `None` if a double hyphen argument is not present or there are no arguments after the double hyphen argument.
     pub double_hyphen_argv: Option<Vec<String>>,
+    /// `Option<Vec<String>>` of ordered arguments that follow the end of option parsing at a double hyphen idiom or, with `parsers::ParseOrder::RequireOrder`, at the first positional argument. `None` if option parsing does not end before the last argument.
+    pub remainder_argv: Option<Vec<String>>,
     /// `usize` that indicates the index position of the last positional option in `Command.argv`
     pub loptind: usize,
+    /// Vector of classified command line arguments with one `parsers::Token` for each argument in `Command.argv`
+    pub tokens: Vec<parsers::Token>,
+    /// HashMap of option values parsed with a `spec::Spec` mapped as key=option key:value=parsed value. Empty if the command was not parsed with a `spec::Spec`.
+    pub values: HashMap<String, spec::Value>,
+    /// HashMap of the configuration layer that defined each option value in `Command.values` mapped as key=option key:value=value source
+    pub value_sources: HashMap<String, spec::ValueSource>,
+    /// Vector of the locations of the values of sensitive options in `Command.argv`
+    pub sensitive_spans: Vec<parsers::Span>,
+    /// Vector of the keys of sensitive options in `Command.values`
+    pub sensitive_options: Vec<String>,
+    /// `Option<Vec<String>>` of the command line arguments before a command alias was expanded with a `spec::AliasTable`. `None` if an alias was not expanded.
+    pub original_argv: Option<Vec<String>>,
 }
 
 // Traits
@@ -74,7 +104,60 @@ pub struct Command {
 // Display trait
 impl fmt::Display for Command {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Command: '{}'", self.argv.join(" "))
+        write!(f, "Command: '{}'", self.redacted().argv.join(" "))
+    }
+}
+
+// Debug trait
+impl fmt::Debug for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let c = self.redacted();
+        f.debug_struct("Command")
+            .field("argv", &c.argv)
+            .field("argc", &c.argc)
+            .field("executable", &c.executable)
+            .field("options", &c.options)
+            .field("definitions", &c.definitions)
+            .field("first_arg", &c.first_arg)
+            .field("last_arg", &c.last_arg)
+            .field("double_hyphen_argv", &c.double_hyphen_argv)
+            .field("remainder_argv", &c.remainder_argv)
+            .field("loptind", &c.loptind)
+            .field("tokens", &c.tokens)
+            .field("values", &c.values)
+            .field("value_sources", &c.value_sources)
+            .field("sensitive_spans", &c.sensitive_spans)
+            .field("sensitive_options", &c.sensitive_options)
+            .field("original_argv", &c.original_argv)
+            .finish()
+    }
+}
+
+// Serialize trait
+#[cfg(feature = "serde")]
+impl serde::Serialize for Command {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let c = self.redacted();
+        let mut state = serializer.serialize_struct("Command", 16)?;
+        state.serialize_field("argv", &c.argv)?;
+        state.serialize_field("argc", &c.argc)?;
+        state.serialize_field("executable", &c.executable)?;
+        state.serialize_field("options", &c.options)?;
+        state.serialize_field("definitions", &c.definitions)?;
+        state.serialize_field("first_arg", &c.first_arg)?;
+        state.serialize_field("last_arg", &c.last_arg)?;
+        state.serialize_field("double_hyphen_argv", &c.double_hyphen_argv)?;
+        state.serialize_field("remainder_argv", &c.remainder_argv)?;
+        state.serialize_field("loptind", &c.loptind)?;
+        state.serialize_field("tokens", &c.tokens)?;
+        state.serialize_field("values", &c.values)?;
+        state.serialize_field("value_sources", &c.value_sources)?;
+        state.serialize_field("sensitive_spans", &c.sensitive_spans)?;
+        state.serialize_field("sensitive_options", &c.sensitive_options)?;
+        state.serialize_field("original_argv", &c.original_argv)?;
+        state.end()
     }
 }
 
@@ -101,36 +184,164 @@ impl Command {
         Command::new_with_vec(std::env::args().collect())
     }
 
+    /// Instantiates and returns a new `Command` struct with the command line argument data in `std::env::args().collect()` that is parsed with the option parsing order `order`
+    ///
+    /// # Remarks
+    ///
+    /// `Command::new()` parses options at any position in the command before a double hyphen idiom (`parsers::ParseOrder::Permute`).  Use `parsers::ParseOrder::RequireOrder` to stop option parsing at the first positional argument so that the options of a wrapped command are not parsed (e.g., `-l` in `[executable] -uroot ls -l`).  The arguments that follow the end of option parsing are defined in `Command.remainder_argv`.  Use `parsers::ParseOrder::from_env()` to respect the `POSIXLY_CORRECT` environment variable.
+    ///
+    /// Without an option specification, the options that take a value are not known, and an option value in a separate argument is the first positional argument (e.g., `root` in `[executable] -u root ls -l`).  Use `spec::Spec::order` with the option definitions to parse the separate values of options before the wrapped command.
+    ///
+    /// `parsers::ParseOrder::ReturnInOrder` defines the same `Command` fields as `parsers::ParseOrder::Permute` because the fields do not record the order of the options and the positional arguments.  Use `parsers::GetOpt` to iterate over the options and the positional arguments in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::parsers::ParseOrder;
+    /// use commandlines::Command;
+    ///
+    /// let c = Command::new_with_order(ParseOrder::RequireOrder);
+    /// ```
+    pub fn new_with_order(order: parsers::ParseOrder) -> Self {
+        Command::new_with_vec_and_order(std::env::args().collect(), order)
+    }
+
+    /// Instantiates and returns a new `Command` struct with the command line argument data in `std::env::args().collect()` that is parsed and validated with the option specification `spec`
+    ///
+    /// Returns a `ParseError` that locates the first argument that is not valid in the specification
+    ///
+    /// # Remarks
+    ///
+    /// The parsed option values are defined in `Command.values`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::spec::{OptionSpec, Spec};
+    /// use commandlines::Command;
+    ///
+    /// let spec = Spec::new("test")
+    ///     .option(OptionSpec::flag("--verbose").short('v'))
+    ///     .option(OptionSpec::value("--output").short('o'));
+    ///
+    /// match Command::new_with_spec(&spec) {
+    ///     Ok(c) => println!("{:?}", c.get_value("--output")),
+    ///     Err(e) => eprintln!("{}", e),
+    /// }
+    /// ```
+    pub fn new_with_spec(spec: &spec::Spec) -> Result<Self, ParseError> {
+        let arguments: Vec<String> = std::env::args().collect();
+        spec.parse(&arguments)
+    }
+
     // Instantiates and returns a new `Command` struct with mocked command line argument data that is passed in the `arguments` argument.
     //
     // # Arguments
     //
     // - arguments: (`Vec<String>`) - a Vector of ordered String items
-    #[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
     fn new_with_vec(arguments: Vec<String>) -> Self {
-        let arguments_definition = arguments.to_owned();
-        let executable_definition = &arguments[0];
+        Command::new_with_vec_and_order(arguments, parsers::ParseOrder::Permute)
+    }
+
+    // Instantiates and returns a new `Command` struct with mocked command line argument data that is passed in the `arguments` argument and parsed with the option parsing order `order`.
+    //
+    // # Arguments
+    //
+    // - arguments: (`Vec<String>`) - a Vector of ordered String items
+    // - order: (`parsers::ParseOrder`) - the option parsing order
+    fn new_with_vec_and_order(arguments: Vec<String>, order: parsers::ParseOrder) -> Self {
+        let tokens = parsers::tokenize_with_order(&arguments, order);
+        Command::new_with_tokens(arguments, tokens)
+    }
+
+    // Instantiates and returns a new `Command` struct with mocked command line argument data that is passed in the `arguments` argument and classified in the `tokens` argument.
+    //
+    // # Arguments
+    //
+    // - arguments: (`Vec<String>`) - a Vector of ordered String items
+    // - tokens: (`Vec<parsers::Token>`) - a Vector with one classified token for each item in `arguments`
+    fn new_with_tokens(arguments: Vec<String>, tokens: Vec<parsers::Token>) -> Self {
+        let executable_definition = arguments[0].clone();
         let size_definition = arguments.len();
-        let vec_options = parsers::parse_options(&arguments);
-        let definitions_hm = parsers::parse_definitions(&arguments);
-        let first_arg_definition = parsers::parse_first_arg(&arguments);
-        let last_arg_definition = parsers::parse_last_arg(&arguments);
-        let double_hyphen_definition = parsers::parse_double_hyphen_args(&arguments);
-        let last_option_index = parsers::parse_loptind_index(&arguments);
+        let vec_options = parsers::options_from_tokens(&tokens);
+        let definitions_hm = parsers::definitions_from_tokens(&tokens);
+        let first_arg_definition = parsers::first_arg_from_tokens(&tokens);
+        let last_arg_definition = parsers::last_arg_from_tokens(&tokens);
+        let double_hyphen_definition = parsers::double_hyphen_args_from_tokens(&tokens);
+        let remainder_definition = parsers::remainder_args_from_tokens(&tokens);
+        let last_option_index = parsers::loptind_index_from_tokens(&tokens);
 
         Command {
-            argv: arguments_definition,
+            argv: arguments,
             argc: size_definition,
-            executable: executable_definition.to_string(),
+            executable: executable_definition,
             options: vec_options,
             definitions: definitions_hm,
             first_arg: first_arg_definition,
             last_arg: last_arg_definition,
             double_hyphen_argv: double_hyphen_definition,
+            remainder_argv: remainder_definition,
             loptind: last_option_index,
+            tokens,
+            values: HashMap::new(),
+            value_sources: HashMap::new(),
+            sensitive_spans: Vec::new(),
+            sensitive_options: Vec::new(),
+            original_argv: None,
         }
     }
 
+    /// Returns `Command` with the values of sensitive options replaced with `spec::REDACTED` in the arguments, tokens, definitions, and option values
+    ///
+    /// # Remarks
+    /// Sensitive options are defined with `spec::OptionSpec::sensitive`.  The `Command` display, debug, and serialized output is redacted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::spec::{OptionSpec, Spec};
+    ///
+    /// let spec = Spec::new("tool").option(OptionSpec::value("--password").sensitive());
+    /// let argv: Vec<String> = vec!["tool", "--password=hunter2"].into_iter().map(String::from).collect();
+    /// let c = spec.parse(&argv).unwrap();
+    ///
+    /// assert_eq!(c.redacted().argv, vec!["tool", "--password=****"]);
+    /// assert_eq!(c.to_string(), "Command: 'tool --password=****'");
+    /// assert_eq!(c.get_value("--password").and_then(|v| v.as_str()), Some("hunter2"));
+    /// ```
+    pub fn redacted(&self) -> Command {
+        let mut c = self.clone();
+        if self.sensitive_spans.is_empty() && self.sensitive_options.is_empty() {
+            return c;
+        }
+        c.argv = spec::redact_spans(&self.argv, &self.sensitive_spans);
+        for token in c.tokens.iter_mut() {
+            if let Some(arg) = c.argv.get(token.index) {
+                token.text = arg.clone();
+            }
+        }
+        c.definitions = parsers::definitions_from_tokens(&c.tokens);
+        c.first_arg = parsers::first_arg_from_tokens(&c.tokens);
+        c.last_arg = parsers::last_arg_from_tokens(&c.tokens);
+        for key in &self.sensitive_options {
+            if let Some(value) = c.values.get_mut(key) {
+                *value = value.redacted();
+            }
+        }
+        if let Some(original) = &self.original_argv {
+            // the arguments that precede and follow the alias are redacted in `argv`
+            let index = (0..original.len())
+                .find(|&i| self.argv.get(i) != original.get(i))
+                .unwrap_or(original.len());
+            let end = (index + 1 + self.argv.len()).saturating_sub(original.len());
+            let mut redacted = c.argv[..index.min(c.argv.len())].to_vec();
+            redacted.extend(original.get(index).cloned());
+            redacted.extend_from_slice(&c.argv[end.min(c.argv.len())..]);
+            c.original_argv = Some(redacted);
+        }
+        c
+    }
+
     /// Returns a boolean for the question "Does the command include any arguments to the executable?"
     ///
     /// # Remarks
@@ -184,10 +395,7 @@ impl Command {
     /// }
     /// ```
     pub fn has_double_hyphen_args(&self) -> bool {
-        match &self.double_hyphen_argv {
-            Some(_) => true,
-            None => false,
-        }
+        self.double_hyphen_argv.is_some()
     }
 
     /// Returns a boolean for the question "Does the command include any invalid options based upon valid options defined in `valid_opts`?"
@@ -290,6 +498,24 @@ impl Command {
         !self.options.is_empty()
     }
 
+    /// Returns a boolean for the question "Does the command include any arguments that follow the end of option parsing?"
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::parsers::ParseOrder;
+    /// use commandlines::Command;
+    ///
+    /// let c = Command::new_with_order(ParseOrder::RequireOrder);
+    ///
+    /// if c.has_remainder_args() {
+    ///     // arguments were identified after the end of option parsing
+    /// }
+    /// ```
+    pub fn has_remainder_args(&self) -> bool {
+        self.remainder_argv.is_some()
+    }
+
     /// Returns a boolean for the question "Does the command include the argument string `needle`?" at any index
     ///
     /// # Examples
@@ -317,10 +543,7 @@ impl Command {
     /// }
     /// ```
     pub fn contains_definition(&self, needle: &str) -> bool {
-        match self.definitions.get(&String::from(needle)) {
-            Some(_) => true,
-            None => false,
-        }
+        self.definitions.contains_key(needle)
     }
 
     /// Returns a boolean for the question "Does the command include the option `needle` when the POSIX multi-option short syntax option style is used?"
@@ -378,9 +601,7 @@ impl Command {
     /// }
     /// ```
     pub fn contains_all_mops(&self, needle_vec: Vec<&str>) -> bool {
-        let some_haystack = parsers::parse_mops(&self.options);
-        if some_haystack.is_some() {
-            let haystack = some_haystack.unwrap();
+        if let Some(haystack) = parsers::parse_mops(&self.options) {
             for needle in needle_vec {
                 if !haystack.contains(&String::from(needle)) {
                     return false;
@@ -429,9 +650,7 @@ impl Command {
     /// }
     /// ```
     pub fn contains_any_mops(&self, needle_vec: Vec<&str>) -> bool {
-        let some_haystack = parsers::parse_mops(&self.options);
-        if some_haystack.is_some() {
-            let haystack = some_haystack.unwrap();
+        if let Some(haystack) = parsers::parse_mops(&self.options) {
             for needle in needle_vec {
                 if haystack.contains(&String::from(needle)) {
                     return true;
@@ -475,7 +694,7 @@ impl Command {
     ///     // the command sequence was identified as "[executable] filter help"
     /// }
     /// ```
-    #[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+    #[allow(clippy::needless_pass_by_value)]
     pub fn contains_sequence(&self, needle_vec: Vec<&str>) -> bool {
         // confirm that the request does not exceed the length of arguments in the command
         // subtract value of 1 for the executable which is excluded in this test
@@ -507,7 +726,7 @@ impl Command {
     ///     None => eprintln!("Missing")
     /// };
     /// ```
-    pub fn get_definition_for(&self, needle: &str) -> Option<Cow<str>> {
+    pub fn get_definition_for(&self, needle: &str) -> Option<Cow<'_, str>> {
         if let Some(x) = self.definitions.get(&String::from(needle)) {
             return Some(Cow::Borrowed(x));
         }
@@ -535,7 +754,7 @@ impl Command {
     ///     None => eprintln!("-o is the last positional argument in the command")
     /// }
     /// ```
-    pub fn get_argument_after(&self, needle: &str) -> Option<Cow<str>> {
+    pub fn get_argument_after(&self, needle: &str) -> Option<Cow<'_, str>> {
         for (index, value) in self.argv.iter().enumerate() {
             if value == needle {
                 if let Some(x) = self.argv.get(index + 1) {
@@ -561,7 +780,7 @@ impl Command {
     ///     None => eprintln!("-o not found or there were no arguments after -o")
     /// }
     /// ```
-    pub fn get_arguments_after(&self, needle: &str) -> Option<Vec<Cow<str>>> {
+    pub fn get_arguments_after(&self, needle: &str) -> Option<Vec<Cow<'_, str>>> {
         for (index, value) in self.argv.iter().enumerate() {
             // test for presence of needle in argv vector
             if value == needle {
@@ -603,7 +822,7 @@ impl Command {
     ///     None => eprintln!("There is no first positional argument")
     /// }
     /// ```
-    pub fn get_argument_at(&self, needle: usize) -> Option<Cow<str>> {
+    pub fn get_argument_at(&self, needle: usize) -> Option<Cow<'_, str>> {
         if let Some(x) = self.argv.get(needle) {
             return Some(Cow::Borrowed(x));
         }
@@ -625,7 +844,7 @@ impl Command {
     ///     None => eprintln!("There are no arguments that follow a double hyphen idiom")
     /// }
     /// ```
-    pub fn get_arguments_after_double_hyphen(&self) -> Option<Vec<Cow<str>>> {
+    pub fn get_arguments_after_double_hyphen(&self) -> Option<Vec<Cow<'_, str>>> {
         if let Some(x) = &self.double_hyphen_argv {
             let mut v: Vec<Cow<str>> = Vec::new();
             for arg in x {
@@ -637,6 +856,154 @@ impl Command {
         None
     }
 
+    /// Returns `Option<Vec<Cow<str>>>` for the arguments in sequence that follow the end of option parsing
+    ///
+    /// Returns `None` if option parsing does not end before the last argument
+    ///
+    /// # Remarks
+    ///
+    /// Option parsing ends at a double hyphen `--` idiom, which is not included in the returned arguments, or, with `parsers::ParseOrder::RequireOrder`, at the first positional argument, which is included in the returned arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::parsers::ParseOrder;
+    /// use commandlines::Command;
+    ///
+    /// let c = Command::new_with_order(ParseOrder::RequireOrder);
+    ///
+    /// match c.get_remainder_args() {
+    ///     Some(x) => println!("Wrapped command: {:?}", x),
+    ///     None => eprintln!("There is no wrapped command")
+    /// }
+    /// ```
+    pub fn get_remainder_args(&self) -> Option<Vec<Cow<'_, str>>> {
+        self.remainder_argv
+            .as_ref()
+            .map(|x| x.iter().map(|arg| Cow::Borrowed(&arg[..])).collect())
+    }
+
+    /// Returns `Option<&spec::Value>` for the option value that was parsed with a `spec::Spec` for the option key `needle`
+    ///
+    /// Returns `None` if the option was not used in the command
+    ///
+    /// # Remarks
+    ///
+    /// The option key is the long option string when a long option is defined in the specification and the short option string when it is not (see `spec::OptionSpec::key`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use commandlines::spec::{OptionSpec, Spec};
+    /// # let spec = Spec::new("test").option(OptionSpec::value("--output").short('o'));
+    /// # let c = spec.parse(&["test".to_string(), "-o".to_string(), "path".to_string()]).unwrap();
+    /// match c.get_value("--output").and_then(|v| v.as_str()) {
+    ///     Some(x) => println!("The output path is {}", x),
+    ///     None => eprintln!("Missing")
+    /// };
+    /// ```
+    pub fn get_value(&self, needle: &str) -> Option<&spec::Value> {
+        self.values.get(needle)
+    }
+
+    /// Returns `Option<&spec::ValueSource>` for the configuration layer (configuration file, environment variable, or command line) that defined the option value for the option key `needle`
+    ///
+    /// Returns `None` if the option value is not defined
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use commandlines::spec::{OptionSpec, Spec, ValueSource};
+    /// # let spec = Spec::new("test").option(OptionSpec::value("--output"));
+    /// # let c = spec.parse(&["test".to_string(), "--output=path".to_string()]).unwrap();
+    /// if let Some(ValueSource::Env(var)) = c.get_value_source("--output") {
+    ///     println!("The output path is defined with the {} environment variable", var);
+    /// }
+    /// ```
+    pub fn get_value_source(&self, needle: &str) -> Option<&spec::ValueSource> {
+        self.value_sources.get(needle)
+    }
+
+    /// Returns `&[String]` with the command line arguments before a command alias was expanded, or `Command.argv` if an alias was not expanded
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use commandlines::spec::{AliasTable, Spec};
+    /// # let spec = Spec::new("test");
+    /// # let argv = vec!["test".to_string(), "co".to_string()];
+    /// let c = AliasTable::new().alias("co", "checkout").parse(&spec, &argv).unwrap();
+    ///
+    /// assert_eq!(c.get_original_argv().join(" "), "test co");
+    /// assert_eq!(c.argv.join(" "), "test checkout");
+    /// ```
+    pub fn get_original_argv(&self) -> &[String] {
+        match &self.original_argv {
+            Some(x) => x,
+            None => &self.argv,
+        }
+    }
+
+    /// Returns `Option<std::process::Command>` that is ready to execute the arguments that follow the end of option parsing as a child process
+    ///
+    /// Returns `None` if option parsing does not end before the last argument
+    ///
+    /// # Remarks
+    ///
+    /// The first argument that follows the end of option parsing is the program and the remaining arguments are the program arguments.  Use `utilities::process::TrailingCommand::from_command` to define a program override, environment variables, or prefix arguments.
+    ///
+    /// # Examples
+    ///
+    /// For a command with the syntax `mytool --fast -- cargo build --release`:
+    ///
+    /// ```
+    /// let c = commandlines::Command::new();
+    ///
+    /// if let Some(mut child) = c.get_trailing_command() {
+    ///     // let status = child.status();
+    /// }
+    /// ```
+    pub fn get_trailing_command(&self) -> Option<std::process::Command> {
+        utilities::process::TrailingCommand::from_command(self).build()
+    }
+
+    /// Returns `Vec<utilities::stdio::InputSource>` with an input for each positional argument and each argument that follows the end of option parsing, where the single hyphen `-` argument is the standard input stream
+    ///
+    /// # Remarks
+    ///
+    /// An argument that follows a double hyphen `--` idiom is a file path, including the single hyphen `-` argument (e.g., `-file` and `-` in `cat -- -file -`).  Option values that are parsed with a `spec::Spec` are not included.  Use `utilities::stdio::InputSource::open_all` to open the inputs.
+    ///
+    /// # Examples
+    ///
+    /// For a command with the syntax `cat a.txt - b.txt`:
+    ///
+    /// ```
+    /// let c = commandlines::Command::new();
+    ///
+    /// for input in c.get_input_sources() {
+    ///     // let reader = input.open()?;
+    /// }
+    /// ```
+    pub fn get_input_sources(&self) -> Vec<utilities::stdio::InputSource> {
+        let mut sources = Vec::new();
+        let mut terminated = false;
+        for token in &self.tokens {
+            match token.kind {
+                parsers::TokenKind::Terminator => terminated = true,
+                parsers::TokenKind::TrailingArg if terminated => sources.push(
+                    utilities::stdio::InputSource::File(std::path::PathBuf::from(&token.text)),
+                ),
+                parsers::TokenKind::Positional
+                | parsers::TokenKind::Stdin
+                | parsers::TokenKind::TrailingArg => {
+                    sources.push(utilities::stdio::InputSource::new(&token.text))
+                }
+                _ => {}
+            }
+        }
+        sources
+    }
+
     /// Returns `Option<Cow<str>>` for the first positional argument to the executable
     ///
     /// Returns `None` if there are no arguments to the executable
@@ -651,7 +1018,7 @@ impl Command {
     ///     None => eprintln!("There are no arguments to the executable")
     /// }
     /// ```
-    pub fn get_argument_first(&self) -> Option<Cow<str>> {
+    pub fn get_argument_first(&self) -> Option<Cow<'_, str>> {
         match &self.first_arg {
             Some(x) => Some(Cow::Borrowed(x)),
             None => None,
@@ -672,7 +1039,7 @@ impl Command {
     ///     None => eprintln!("There are no arguments to the executable")
     /// }
     /// ```
-    pub fn get_argument_last(&self) -> Option<Cow<str>> {
+    pub fn get_argument_last(&self) -> Option<Cow<'_, str>> {
         match &self.last_arg {
             Some(x) => Some(Cow::Borrowed(x)),
             None => None,
@@ -689,7 +1056,7 @@ impl Command {
     /// println!("{} v1.0.0", c.get_executable())
     ///
     /// ```
-    pub fn get_executable(&self) -> Cow<str> {
+    pub fn get_executable(&self) -> Cow<'_, str> {
         Cow::Borrowed(&self.executable)
     }
 
@@ -775,6 +1142,115 @@ impl Command {
     pub fn is_usage_request(&self) -> bool {
         self.options.contains(&"--usage".to_string())
     }
+
+    /// Returns `String` with the message for `error` followed by the command and a caret line that marks the location of the argument that caused the error
+    ///
+    /// # Remarks
+    /// The values of the sensitive options of a `spec::Spec` are redacted in the command.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let c = commandlines::Command::new();
+    ///
+    /// if let Err(e) = c.validate_options(&["-h", "--help"]) {
+    ///     eprintln!("{}", c.render_error(&e));
+    /// }
+    /// ```
+    pub fn render_error(&self, error: &ParseError) -> String {
+        errors::render_redacted_error(&self.argv, &self.sensitive_spans, error)
+    }
+
+    /// Returns `Result<(), ParseError>` with an error that locates the first invalid definition option based upon valid definition options defined in `valid_defs`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// const VALIDDEFS: [&str; 2] = [
+    ///       "-o",
+    ///       "--output",
+    /// ];
+    ///
+    /// let c = commandlines::Command::new();
+    ///
+    /// if let Err(e) = c.validate_definitions(&VALIDDEFS) {
+    ///     eprintln!("{}", c.render_error(&e));
+    /// }
+    /// ```
+    pub fn validate_definitions(&self, valid_defs: &[&str]) -> Result<(), ParseError> {
+        for token in &self.tokens {
+            if let (Some(option), Some(_)) = (token.option(), token.definition()) {
+                if !valid_defs.contains(&option) {
+                    return Err(ParseError::new(
+                        ErrorKind::InvalidDefinition(option.to_string()),
+                        token.option_span(),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns `Result<(), ParseError>` with an error that locates the first invalid option based upon valid options defined in `valid_opts`
+    ///
+    /// # Remarks
+    /// Each switch in a multi-option short syntax style option (e.g., `-lmn`) that is not itself defined in `valid_opts` is validated individually so that the error locates the invalid switch character (e.g., the `o` inside `-xvo`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// const VALIDOPTS: [&str; 3] = [
+    ///       "-h",
+    ///       "--help",
+    ///       "-v",
+    /// ];
+    ///
+    /// let c = commandlines::Command::new();
+    ///
+    /// if let Err(e) = c.validate_options(&VALIDOPTS) {
+    ///     eprintln!("{}", c.render_error(&e));
+    /// }
+    /// ```
+    pub fn validate_options(&self, valid_opts: &[&str]) -> Result<(), ParseError> {
+        for token in &self.tokens {
+            let option = match token.option() {
+                Some(x) => x,
+                None => continue,
+            };
+            if valid_opts.contains(&option) {
+                continue;
+            }
+            match token.kind {
+                parsers::TokenKind::ShortCluster | parsers::TokenKind::ShortWithValue
+                    if parsers::is_mops_option(option) =>
+                {
+                    for (switch, span) in token.short_options() {
+                        if !valid_opts.contains(&&switch[..]) {
+                            return Err(ParseError::new(
+                                ErrorKind::InvalidOption(switch),
+                                Some(span),
+                            ));
+                        }
+                    }
+                }
+                _ => {
+                    return Err(ParseError::new(
+                        ErrorKind::InvalidOption(option.to_string()),
+                        token.option_span(),
+                    ))
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Returns the owned command line arguments in `args` for the tests of the crate modules
+#[cfg(test)]
+fn argv(args: &[&str]) -> Vec<String> {
+    args.iter().map(|x| x.to_string()).collect()
 }
 
 // Tests
@@ -919,6 +1395,59 @@ mod tests {
         assert_eq!(c.double_hyphen_argv, None);
     }
 
+    #[test]
+    fn command_instantiation_remainder_argv() {
+        let args = vec![
+            "sudo".to_string(),
+            "-u".to_string(),
+            "root".to_string(),
+            "ls".to_string(),
+            "-l".to_string(),
+            "--".to_string(),
+            "-a".to_string(),
+        ];
+        let c1 = Command::new_with_vec(args.clone());
+        let c2 = Command::new_with_vec_and_order(args.clone(), parsers::ParseOrder::RequireOrder);
+        let c3 = Command::new_with_vec_and_order(args.clone(), parsers::ParseOrder::ReturnInOrder);
+
+        assert_eq!(c1.remainder_argv, Some(vec!["-a".to_string()]));
+        assert_eq!(c1.options, vec!["-u".to_string(), "-l".to_string()]);
+        assert_eq!(c1.loptind, 4);
+
+        // without an option specification, the separate `-u` value ends option parsing
+        assert_eq!(c2.remainder_argv, Some(args[2..].to_vec()));
+        assert_eq!(c2.options, vec!["-u".to_string()]); // wrapped command options are not parsed
+        assert_eq!(c2.double_hyphen_argv, None); // `--` follows the end of option parsing
+        assert_eq!(c2.loptind, 1);
+
+        assert_eq!(c3.remainder_argv, c1.remainder_argv);
+        assert_eq!(c3.options, c1.options);
+
+        let c4 = Command::new_with_vec_and_order(
+            vec![
+                "sudo".to_string(),
+                "-uroot".to_string(),
+                "ls".to_string(),
+                "-l".to_string(),
+            ],
+            parsers::ParseOrder::RequireOrder,
+        );
+        assert_eq!(
+            c4.remainder_argv,
+            Some(vec!["ls".to_string(), "-l".to_string()])
+        );
+        assert_eq!(c4.options, vec!["-uroot".to_string()]);
+    }
+
+    #[test]
+    fn command_instantiation_remainder_argv_none() {
+        let c = Command::new_with_vec_and_order(
+            vec!["test".to_string(), "-o".to_string(), "--".to_string()],
+            parsers::ParseOrder::RequireOrder,
+        );
+        assert_eq!(c.remainder_argv, None);
+    }
+
     #[test]
     fn command_instantiation_loptind_with_opts() {
         let test_vec: Vec<String> = vec![
@@ -1410,6 +1939,92 @@ mod tests {
         assert_eq!(c3.get_arguments_after_double_hyphen(), None);
     }
 
+    #[test]
+    fn command_method_has_and_get_remainder_args() {
+        let c1 = Command::new_with_vec_and_order(
+            vec![
+                "env".to_string(),
+                "-i".to_string(),
+                "cargo".to_string(),
+                "--release".to_string(),
+            ],
+            parsers::ParseOrder::RequireOrder,
+        );
+        let c2 = Command::new_with_vec_and_order(
+            vec!["env".to_string(), "-i".to_string()],
+            parsers::ParseOrder::RequireOrder,
+        );
+
+        assert_eq!(c1.has_remainder_args(), true);
+        assert_eq!(
+            c1.get_remainder_args(),
+            Some(vec![Cow::Borrowed("cargo"), Cow::Borrowed("--release")])
+        );
+        assert_eq!(c2.has_remainder_args(), false);
+        assert_eq!(c2.get_remainder_args(), None);
+    }
+
+    #[test]
+    fn command_method_get_trailing_command() {
+        let c1 = Command::new_with_vec(vec![
+            "mytool".to_string(),
+            "--fast".to_string(),
+            "--".to_string(),
+            "cargo".to_string(),
+            "build".to_string(),
+        ]);
+        let c2 = Command::new_with_vec(vec!["mytool".to_string(), "--fast".to_string()]);
+
+        let child = c1.get_trailing_command().unwrap();
+        assert_eq!(child.get_program(), "cargo");
+        assert_eq!(child.get_args().collect::<Vec<_>>(), vec!["build"]);
+        assert!(c2.get_trailing_command().is_none());
+    }
+
+    #[test]
+    fn command_method_get_input_sources() {
+        use utilities::stdio::InputSource;
+
+        let c1 = Command::new_with_vec(vec![
+            "cat".to_string(),
+            "-n".to_string(),
+            "a.txt".to_string(),
+            "-".to_string(),
+            "--".to_string(),
+            "-b.txt".to_string(),
+            "-".to_string(),
+        ]);
+        let c2 = Command::new_with_vec(vec!["cat".to_string(), "-n".to_string()]);
+        let c3 = Command::new_with_vec_and_order(
+            vec![
+                "cat".to_string(),
+                "a.txt".to_string(),
+                "-n".to_string(),
+                "-".to_string(),
+            ],
+            parsers::ParseOrder::RequireOrder,
+        );
+
+        assert_eq!(
+            c1.get_input_sources(),
+            vec![
+                InputSource::new("a.txt"),
+                InputSource::Stdin,
+                InputSource::new("-b.txt"),
+                InputSource::File(std::path::PathBuf::from("-"))
+            ]
+        );
+        assert_eq!(c2.get_input_sources(), Vec::<InputSource>::new());
+        assert_eq!(
+            c3.get_input_sources(),
+            vec![
+                InputSource::new("a.txt"),
+                InputSource::new("-n"),
+                InputSource::Stdin
+            ]
+        );
+    }
+
     #[test]
     fn command_method_get_argument_first() {
         let c1 = Command::new_with_vec(vec!["test".to_string(), "-o".to_string()]);
@@ -1533,6 +2148,26 @@ mod tests {
         assert_eq!(c7.is_version_request(), false);
     }
 
+    #[test]
+    fn command_instantiation_tokens_field() {
+        let c = Command::new_with_vec(vec![
+            "test".to_string(),
+            "-o".to_string(),
+            "--".to_string(),
+            "-x".to_string(),
+        ]);
+        let kinds: Vec<parsers::TokenKind> = c.tokens.iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                parsers::TokenKind::Executable,
+                parsers::TokenKind::ShortFlag,
+                parsers::TokenKind::Terminator,
+                parsers::TokenKind::TrailingArg,
+            ]
+        );
+    }
+
     #[test]
     fn command_method_is_usage_request() {
         let c1 = Command::new_with_vec(vec!["test".to_string(), "--usage".to_string()]);
@@ -1547,4 +2182,124 @@ mod tests {
         assert_eq!(c4.is_usage_request(), false);
         assert_eq!(c5.is_usage_request(), false);
     }
+
+    #[test]
+    fn command_method_render_error() {
+        let c = Command::new_with_vec(vec![
+            "test".to_string(),
+            "-xvo".to_string(),
+            "file".to_string(),
+        ]);
+        let e = c.validate_options(&["-x", "-v"]).unwrap_err();
+        assert_eq!(
+            c.render_error(&e),
+            "error: invalid option '-o'\n  test -xvo file\n          ^"
+        );
+    }
+
+    #[test]
+    fn command_method_validate_definitions() {
+        let valid_defs: [&str; 2] = ["-o", "--output"];
+        let c1 = Command::new_with_vec(vec!["test".to_string(), "--output=test".to_string()]);
+        let c2 = Command::new_with_vec(vec![
+            "test".to_string(),
+            "-o=test".to_string(),
+            "--other=bogus".to_string(),
+        ]);
+        let c3 = Command::new_with_vec(vec!["test".to_string(), "--other".to_string()]);
+
+        assert_eq!(c1.validate_definitions(&valid_defs), Ok(()));
+        assert_eq!(
+            c2.validate_definitions(&valid_defs),
+            Err(ParseError::new(
+                ErrorKind::InvalidDefinition("--other".to_string()),
+                Some(parsers::Span {
+                    index: 2,
+                    start: 0,
+                    end: 7
+                })
+            ))
+        );
+        assert_eq!(c3.validate_definitions(&valid_defs), Ok(())); // not a definition option
+    }
+
+    #[test]
+    fn command_method_validate_options() {
+        let valid_opts: [&str; 4] = ["-h", "--help", "-x", "-v"];
+        let c1 = Command::new_with_vec(vec![
+            "test".to_string(),
+            "--help".to_string(),
+            "-xv".to_string(),
+            "-".to_string(),
+            "--".to_string(),
+            "--bogus".to_string(),
+        ]);
+        let c2 = Command::new_with_vec(vec![
+            "test".to_string(),
+            "-h".to_string(),
+            "-xvo".to_string(),
+        ]);
+        let c3 = Command::new_with_vec(vec!["test".to_string(), "--output=path".to_string()]);
+
+        assert_eq!(c1.validate_options(&valid_opts), Ok(()));
+        assert_eq!(
+            c2.validate_options(&valid_opts),
+            Err(ParseError::new(
+                ErrorKind::InvalidOption("-o".to_string()),
+                Some(parsers::Span {
+                    index: 2,
+                    start: 3,
+                    end: 4
+                })
+            ))
+        );
+        assert_eq!(
+            c3.validate_options(&valid_opts),
+            Err(ParseError::new(
+                ErrorKind::InvalidOption("--output".to_string()),
+                Some(parsers::Span {
+                    index: 1,
+                    start: 0,
+                    end: 8
+                })
+            ))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn command_serde_json_round_trip() {
+        let spec = spec::Spec::new("tool")
+            .option(spec::OptionSpec::value("--output").short('o'))
+            .option(spec::OptionSpec::count("-v"))
+            .option(spec::OptionSpec::map("-D"));
+        let argv: Vec<String> = vec![
+            "tool", "-vv", "-o", "out.txt", "-DA=1", "in.txt", "--", "-x",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        let c = spec.parse(&argv).unwrap();
+        let json = serde_json::to_string(&c).unwrap();
+        let reloaded: Command = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(reloaded, c);
+        assert!(json.contains("\"double_hyphen_argv\":[\"-x\"]"));
+        assert!(json.contains("\"--output\":{\"Single\":\"out.txt\"}"));
+        assert!(json.contains("\"-v\":\"Argv\""));
+
+        let error = spec.parse(&argv[..3]).unwrap_err();
+        let json = serde_json::to_string(&error).unwrap();
+        assert_eq!(serde_json::from_str::<ParseError>(&json).unwrap(), error);
+
+        let spec = spec.option(spec::OptionSpec::value("--password").sensitive());
+        let c = spec
+            .parse(&[String::from("tool"), String::from("--password=hunter2")])
+            .unwrap();
+        let json = serde_json::to_string(&c).unwrap();
+        let reloaded: Command = serde_json::from_str(&json).unwrap();
+
+        assert!(!json.contains("hunter2"));
+        assert_eq!(reloaded, c.redacted());
+    }
 }
@@ -0,0 +1,571 @@
+// Copyright 2018 Christopher Simpkins
+// Licensed under the MIT license
+
+//! getopt compatible command line option iteration
+
+use std::error::Error;
+use std::fmt;
+
+use parsers::ParseOrder;
+
+/// The argument requirement of an option, equivalent to the `no_argument`, `required_argument`, and `optional_argument` values of the C `struct option` `has_arg` field
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HasArg {
+    /// The option does not take an argument (e.g., `a` in an optstring)
+    No,
+    /// The option requires an argument (e.g., `b:` in an optstring)
+    Required,
+    /// The option takes an optional argument that must be attached to the option (e.g., `c::` in an optstring)
+    Optional,
+}
+
+/// A long option definition, equivalent to the C `struct option` used with `getopt_long`
+#[derive(Clone, Debug, PartialEq)]
+pub struct LongOpt {
+    /// The long option name without the leading double hyphen
+    pub name: String,
+    /// The argument requirement of the option
+    pub has_arg: HasArg,
+    /// The short option character that is returned for the long option. `None` returns the long option name.
+    pub val: Option<char>,
+}
+
+impl LongOpt {
+    /// Instantiates and returns a new `LongOpt` struct for the long option `name` without the leading double hyphen
+    pub fn new(name: &str, has_arg: HasArg) -> Self {
+        LongOpt {
+            name: name.to_string(),
+            has_arg,
+            val: None,
+        }
+    }
+
+    /// Returns the `LongOpt` with the short option character `val` returned in place of the long option name
+    pub fn val(mut self, val: char) -> Self {
+        self.val = Some(val);
+        self
+    }
+}
+
+/// An option that is returned by the `GetOpt` iterator
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Opt {
+    /// A short option character (e.g., `a` for `-a`)
+    Short(char),
+    /// A long option name without the leading double hyphen (e.g., `verbose` for `--verbose`)
+    Long(String),
+    /// An operand that is returned in order with the options with `ParseOrder::ReturnInOrder` (C getopt `1`)
+    Operand,
+}
+
+// Display trait
+impl fmt::Display for Opt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Opt::Short(c) => write!(f, "-{}", c),
+            Opt::Long(name) => write!(f, "--{}", name),
+            Opt::Operand => write!(f, "operand"),
+        }
+    }
+}
+
+/// An option error that is returned by the `GetOpt` iterator in place of the C getopt `?` and `:` return values
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GetOptError {
+    /// The option is not defined (C getopt `?`)
+    UnknownOption(Opt),
+    /// The option requires an argument that is missing (C getopt `:`)
+    MissingArgument(Opt),
+    /// The long option does not take an argument but was defined with one (e.g., `--verbose=yes`)
+    UnexpectedArgument(Opt),
+    /// The abbreviated long option matches more than one long option definition
+    AmbiguousOption(String),
+}
+
+// Display trait
+impl fmt::Display for GetOptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GetOptError::UnknownOption(opt) => write!(f, "invalid option '{}'", opt),
+            GetOptError::MissingArgument(opt) => {
+                write!(f, "option '{}' requires an argument", opt)
+            }
+            GetOptError::UnexpectedArgument(opt) => {
+                write!(f, "option '{}' doesn't allow an argument", opt)
+            }
+            GetOptError::AmbiguousOption(name) => write!(f, "option '--{}' is ambiguous", name),
+        }
+    }
+}
+
+// Error trait
+impl Error for GetOptError {}
+
+/// An iterator over the options in a command line that follows the C `getopt` and `getopt_long` conventions
+///
+/// Each item is a `(Opt, Option<String>)` tuple with the option and its argument, or a `GetOptError` for an invalid option.  Options are parsed from index position `1`, following the executable.  Arguments that are not options are collected as operands and are available from `GetOpt::operands` when the iteration is complete.
+///
+/// Options are parsed with the `ParseOrder::Permute` order by default.  The order is defined with the `GetOpt::order` method or with a `+` (`ParseOrder::RequireOrder`) or `-` (`ParseOrder::ReturnInOrder`) optstring prefix.  Use `ParseOrder::from_env()` to respect the `POSIXLY_CORRECT` environment variable.
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::parsers::{GetOpt, HasArg, LongOpt, Opt};
+///
+/// let argv: Vec<String> = vec!["test", "-a", "-b", "path", "--verbose", "file"]
+///     .into_iter()
+///     .map(String::from)
+///     .collect();
+/// let mut getopt = GetOpt::new(&argv, "ab:c::")
+///     .long_options(vec![LongOpt::new("verbose", HasArg::No)]);
+///
+/// while let Some(item) = getopt.next() {
+///     match item {
+///         Ok((Opt::Short('a'), _)) => {}
+///         Ok((Opt::Short('b'), Some(path))) => assert_eq!(path, "path"),
+///         Ok((Opt::Long(name), None)) => assert_eq!(name, "verbose"),
+///         Ok(_) => {}
+///         Err(e) => eprintln!("{}", e),
+///     }
+/// }
+/// assert_eq!(getopt.operands(), &["file".to_string()]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct GetOpt {
+    argv: Vec<String>,
+    short_options: Vec<(char, HasArg)>,
+    long_options: Vec<LongOpt>,
+    optind: usize,
+    nextchar: usize,
+    last_option_index: usize,
+    operands: Vec<String>,
+    order: ParseOrder,
+    finished: bool,
+}
+
+impl GetOpt {
+    /// Instantiates and returns a new `GetOpt` iterator for the command line arguments in `argv` with short options defined in the getopt `optstring` format (e.g., `"ab:c::"`)
+    ///
+    /// # Remarks
+    /// A character followed by one colon requires an argument and a character followed by two colons takes an optional argument.  A leading `+` or `-` defines the `ParseOrder::RequireOrder` or `ParseOrder::ReturnInOrder` parsing order.  A leading colon is accepted for compatibility and has no effect because errors are always returned as `GetOptError` values.
+    pub fn new(argv: &[String], optstring: &str) -> Self {
+        let order = if optstring.starts_with('+') {
+            ParseOrder::RequireOrder
+        } else if optstring.starts_with('-') {
+            ParseOrder::ReturnInOrder
+        } else {
+            ParseOrder::Permute
+        };
+        GetOpt {
+            argv: argv.to_vec(),
+            short_options: parse_optstring(optstring),
+            long_options: Vec::new(),
+            optind: 1,
+            nextchar: 0,
+            last_option_index: 0,
+            operands: Vec::new(),
+            order,
+            finished: false,
+        }
+    }
+
+    /// Returns the `GetOpt` iterator with the long option definitions in `long_options`
+    pub fn long_options(mut self, long_options: Vec<LongOpt>) -> Self {
+        self.long_options = long_options;
+        self
+    }
+
+    /// Returns the `GetOpt` iterator with the option parsing order `order`
+    pub fn order(mut self, order: ParseOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Returns `usize` with the index position of the next argument to be processed, equivalent to the C getopt `optind` variable
+    ///
+    /// # Remarks
+    /// With `ParseOrder::RequireOrder`, the index position of the first operand is returned when the iteration is complete.
+    pub fn optind(&self) -> usize {
+        self.optind
+    }
+
+    /// Returns `usize` with the index position of the last option argument that was processed.
+    /// Returns `0` if no options have been processed.
+    ///
+    /// # Remarks
+    /// When the iteration is complete, this value is consistent with `Command.loptind` for commands that do not use option arguments beginning with a hyphen.
+    pub fn last_option_index(&self) -> usize {
+        self.last_option_index
+    }
+
+    /// Returns `&[String]` with the operands (arguments that are not options or option arguments) that have been processed, including all arguments that follow a double hyphen `--` idiom
+    pub fn operands(&self) -> &[String] {
+        &self.operands
+    }
+
+    // Returns the next long option item for the argument `arg` without the leading double hyphen
+    fn next_long(&mut self, arg: &str) -> Result<(Opt, Option<String>), GetOptError> {
+        let (name, value) = match arg.find('=') {
+            Some(i) => (&arg[..i], Some(arg[(i + 1)..].to_string())),
+            None => (arg, None),
+        };
+        self.last_option_index = self.optind;
+        self.optind += 1;
+
+        let long_option = self.find_long_option(name)?;
+        let opt = match long_option.val {
+            Some(c) => Opt::Short(c),
+            None => Opt::Long(long_option.name.clone()),
+        };
+        match long_option.has_arg {
+            HasArg::No if value.is_some() => Err(GetOptError::UnexpectedArgument(opt)),
+            HasArg::No | HasArg::Optional => Ok((opt, value)),
+            HasArg::Required => {
+                if value.is_some() {
+                    return Ok((opt, value));
+                }
+                match self.argv.get(self.optind) {
+                    Some(x) => {
+                        self.optind += 1;
+                        Ok((opt, Some(x.clone())))
+                    }
+                    None => Err(GetOptError::MissingArgument(opt)),
+                }
+            }
+        }
+    }
+
+    // Returns the `LongOpt` definition that matches `name` exactly or as an unambiguous abbreviation
+    fn find_long_option(&self, name: &str) -> Result<LongOpt, GetOptError> {
+        if let Some(x) = self.long_options.iter().find(|x| x.name == name) {
+            return Ok(x.clone());
+        }
+        let candidates: Vec<&LongOpt> = self
+            .long_options
+            .iter()
+            .filter(|x| !name.is_empty() && x.name.starts_with(name))
+            .collect();
+        match candidates.len() {
+            0 => Err(GetOptError::UnknownOption(Opt::Long(name.to_string()))),
+            1 => Ok(candidates[0].clone()),
+            _ => Err(GetOptError::AmbiguousOption(name.to_string())),
+        }
+    }
+
+    // Returns the next short option item in the argument at `self.optind`
+    fn next_short(&mut self) -> Result<(Opt, Option<String>), GetOptError> {
+        let arg = self.argv[self.optind].clone();
+        let c = arg[self.nextchar..].chars().next().unwrap_or('-');
+        self.nextchar += c.len_utf8();
+        self.last_option_index = self.optind;
+        let at_end = self.nextchar >= arg.len();
+
+        let has_arg = match self.short_options.iter().find(|x| x.0 == c) {
+            Some(x) => x.1,
+            None => {
+                self.advance_if(at_end);
+                return Err(GetOptError::UnknownOption(Opt::Short(c)));
+            }
+        };
+        match has_arg {
+            HasArg::No => {
+                self.advance_if(at_end);
+                Ok((Opt::Short(c), None))
+            }
+            HasArg::Optional => {
+                let value = if at_end {
+                    None
+                } else {
+                    Some(arg[self.nextchar..].to_string())
+                };
+                self.advance_if(true);
+                Ok((Opt::Short(c), value))
+            }
+            HasArg::Required => {
+                if !at_end {
+                    let value = arg[self.nextchar..].to_string();
+                    self.advance_if(true);
+                    return Ok((Opt::Short(c), Some(value)));
+                }
+                self.advance_if(true);
+                match self.argv.get(self.optind) {
+                    Some(x) => {
+                        self.optind += 1;
+                        Ok((Opt::Short(c), Some(x.clone())))
+                    }
+                    None => Err(GetOptError::MissingArgument(Opt::Short(c))),
+                }
+            }
+        }
+    }
+
+    // Moves to the next argument when `condition` is true
+    fn advance_if(&mut self, condition: bool) {
+        if condition {
+            self.optind += 1;
+            self.nextchar = 0;
+        }
+    }
+}
+
+impl Iterator for GetOpt {
+    type Item = Result<(Opt, Option<String>), GetOptError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.nextchar > 0 {
+            return Some(self.next_short());
+        }
+        if self.finished {
+            return None;
+        }
+        while let Some(arg) = self.argv.get(self.optind).cloned() {
+            if arg == "--" {
+                // Per POSIX guidelines, all arguments that follow the double hyphen are operands
+                self.operands
+                    .extend_from_slice(&self.argv[(self.optind + 1)..]);
+                self.optind = self.argv.len();
+                self.finished = true;
+                return None;
+            } else if let Some(long) = arg.strip_prefix("--") {
+                return Some(self.next_long(long));
+            } else if arg.starts_with('-') && arg != "-" {
+                self.nextchar = 1;
+                return Some(self.next_short());
+            }
+            match self.order {
+                ParseOrder::Permute => {
+                    self.operands.push(arg);
+                    self.optind += 1;
+                }
+                ParseOrder::RequireOrder => {
+                    // option parsing stops at the first operand
+                    self.operands.extend_from_slice(&self.argv[self.optind..]);
+                    self.finished = true;
+                    return None;
+                }
+                ParseOrder::ReturnInOrder => {
+                    self.optind += 1;
+                    return Some(Ok((Opt::Operand, Some(arg))));
+                }
+            }
+        }
+
+        self.finished = true;
+        None
+    }
+}
+
+// Returns `Vec<(char, HasArg)>` with the short option definitions in a getopt `optstring`
+fn parse_optstring(optstring: &str) -> Vec<(char, HasArg)> {
+    let mut short_options: Vec<(char, HasArg)> = Vec::new();
+    let chars: Vec<char> = optstring
+        .trim_start_matches(&['+', '-', ':'][..])
+        .chars()
+        .collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let mut has_arg = HasArg::No;
+        if chars.get(i + 1) == Some(&':') {
+            has_arg = HasArg::Required;
+            i += 1;
+            if chars.get(i + 1) == Some(&':') {
+                has_arg = HasArg::Optional;
+                i += 1;
+            }
+        }
+        short_options.push((c, has_arg));
+        i += 1;
+    }
+
+    short_options
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argv;
+
+    #[test]
+    fn function_parse_optstring() {
+        assert_eq!(
+            parse_optstring(":ab:c::"),
+            vec![
+                ('a', HasArg::No),
+                ('b', HasArg::Required),
+                ('c', HasArg::Optional),
+            ]
+        );
+    }
+
+    #[test]
+    fn getopt_short_options() {
+        let test_vec = argv(&[
+            "test", "-a", "-bpath", "-b", "other", "-c", "-cval", "-ab", "x",
+        ]);
+        let items: Vec<_> = GetOpt::new(&test_vec, "ab:c::").collect();
+        assert_eq!(
+            items,
+            vec![
+                Ok((Opt::Short('a'), None)),
+                Ok((Opt::Short('b'), Some("path".to_string()))),
+                Ok((Opt::Short('b'), Some("other".to_string()))),
+                Ok((Opt::Short('c'), None)), // optional argument is never taken from the next argument
+                Ok((Opt::Short('c'), Some("val".to_string()))),
+                Ok((Opt::Short('a'), None)),
+                Ok((Opt::Short('b'), Some("x".to_string()))),
+            ]
+        );
+    }
+
+    #[test]
+    fn getopt_short_option_errors() {
+        let test_vec = argv(&["test", "-az", "-b"]);
+        let items: Vec<_> = GetOpt::new(&test_vec, "ab:").collect();
+        assert_eq!(
+            items,
+            vec![
+                Ok((Opt::Short('a'), None)),
+                Err(GetOptError::UnknownOption(Opt::Short('z'))),
+                Err(GetOptError::MissingArgument(Opt::Short('b'))),
+            ]
+        );
+    }
+
+    #[test]
+    fn getopt_long_options() {
+        let test_vec = argv(&[
+            "test",
+            "--verbose",
+            "--output=path",
+            "--output",
+            "other",
+            "--color",
+            "--color=never",
+            "--verb",
+            "--size=2",
+        ]);
+        let items: Vec<_> = GetOpt::new(&test_vec, "")
+            .long_options(vec![
+                LongOpt::new("verbose", HasArg::No),
+                LongOpt::new("output", HasArg::Required),
+                LongOpt::new("color", HasArg::Optional),
+                LongOpt::new("size", HasArg::Required).val('s'),
+            ])
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                Ok((Opt::Long("verbose".to_string()), None)),
+                Ok((Opt::Long("output".to_string()), Some("path".to_string()))),
+                Ok((Opt::Long("output".to_string()), Some("other".to_string()))),
+                Ok((Opt::Long("color".to_string()), None)),
+                Ok((Opt::Long("color".to_string()), Some("never".to_string()))),
+                Ok((Opt::Long("verbose".to_string()), None)), // unambiguous abbreviation
+                Ok((Opt::Short('s'), Some("2".to_string()))),
+            ]
+        );
+    }
+
+    #[test]
+    fn getopt_long_option_errors() {
+        let test_vec = argv(&["test", "--bogus", "--verbose=yes", "--ver", "--output"]);
+        let items: Vec<_> = GetOpt::new(&test_vec, "")
+            .long_options(vec![
+                LongOpt::new("verbose", HasArg::No),
+                LongOpt::new("version", HasArg::No),
+                LongOpt::new("output", HasArg::Required),
+            ])
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                Err(GetOptError::UnknownOption(Opt::Long("bogus".to_string()))),
+                Err(GetOptError::UnexpectedArgument(Opt::Long(
+                    "verbose".to_string()
+                ))),
+                Err(GetOptError::AmbiguousOption("ver".to_string())),
+                Err(GetOptError::MissingArgument(Opt::Long(
+                    "output".to_string()
+                ))),
+            ]
+        );
+    }
+
+    #[test]
+    fn getopt_operands_and_double_hyphen() {
+        let test_vec = argv(&["test", "first", "-a", "-", "second", "--", "-b", "last"]);
+        let mut getopt = GetOpt::new(&test_vec, "ab");
+        assert_eq!(getopt.next(), Some(Ok((Opt::Short('a'), None))));
+        assert_eq!(getopt.optind(), 3);
+        assert_eq!(getopt.next(), None);
+        assert_eq!(getopt.optind(), test_vec.len());
+        assert_eq!(
+            getopt.operands(),
+            &argv(&["first", "-", "second", "-b", "last"])[..]
+        );
+    }
+
+    #[test]
+    fn getopt_require_order() {
+        let test_vec = argv(&["sudo", "-u", "root", "ls", "-l", "--", "-a"]);
+        let items: Vec<_> = GetOpt::new(&test_vec, "u:l")
+            .order(ParseOrder::RequireOrder)
+            .collect();
+        assert_eq!(items, vec![Ok((Opt::Short('u'), Some("root".to_string())))]);
+
+        let mut getopt = GetOpt::new(&test_vec, "+u:l");
+        while getopt.next().is_some() {}
+        assert_eq!(getopt.optind(), 3); // index position of the first operand
+        assert_eq!(getopt.operands(), &test_vec[3..]);
+        assert_eq!(getopt.next(), None);
+    }
+
+    #[test]
+    fn getopt_return_in_order() {
+        let test_vec = argv(&["test", "first", "-a", "second", "--", "-b"]);
+        let mut getopt = GetOpt::new(&test_vec, "-ab");
+        assert_eq!(
+            getopt.next(),
+            Some(Ok((Opt::Operand, Some("first".to_string()))))
+        );
+        assert_eq!(getopt.next(), Some(Ok((Opt::Short('a'), None))));
+        assert_eq!(
+            getopt.next(),
+            Some(Ok((Opt::Operand, Some("second".to_string()))))
+        );
+        assert_eq!(getopt.next(), None);
+        assert_eq!(getopt.operands(), &argv(&["-b"])[..]);
+    }
+
+    #[test]
+    fn function_parse_optstring_order_prefix() {
+        assert_eq!(parse_optstring("+:a"), vec![('a', HasArg::No)]);
+        assert_eq!(parse_optstring("-b:"), vec![('b', HasArg::Required)]);
+    }
+
+    #[test]
+    fn getopt_last_option_index_consistent_with_command_loptind() {
+        let test_vec = argv(&[
+            "test",
+            "subcmd",
+            "-l",
+            "--last",
+            "--output=path",
+            "-",
+            "--",
+            "--nonparse",
+            "lastpos",
+        ]);
+        let mut getopt = GetOpt::new(&test_vec, "l").long_options(vec![
+            LongOpt::new("last", HasArg::No),
+            LongOpt::new("output", HasArg::Required),
+        ]);
+        while getopt.next().is_some() {}
+        assert_eq!(
+            getopt.last_option_index(),
+            ::parsers::parse_loptind_index(&test_vec)
+        );
+    }
+}
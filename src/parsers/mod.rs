@@ -3,60 +3,292 @@
 
 //! Command line string parsing support
 
+pub mod getopt;
+pub mod values;
+pub mod words;
+
+pub use self::getopt::{GetOpt, GetOptError, HasArg, LongOpt, Opt};
+pub use self::words::{split_word_spans, split_words, split_words_partial, Word};
+
 use std::collections::HashMap;
 
-/// Returns `Vec<String>` of command line option arguments in a command line string.
-pub fn parse_options(argv: &[String]) -> Vec<String> {
-    let mut options: Vec<String> = Vec::new();
-    for arg in argv {
-        if arg.starts_with('-') {
-            // test to confirm that this is not a single hyphen argument
-            // Per POSIX guidelines, the single hyphen is used to represent
-            // stdin/stdout and should not be parsed as an option
-            if arg == "-" {
-                continue;
-            }
-            // test to confirm that we haven't encountered a double
-            // hyphen command line argument
-            // Per POSIX guidelines, the double hyphen indicates that all
-            // subsequent argument parsing for options should be ignored
-            if is_double_hyphen_option(&arg[..]) {
-                break;
-            }
-            // test for a definition formatted option (e.g., `--option=definition`)
-            // parse to option and definition parts if identified
-            if is_definition_option(&arg[..]) {
-                let option_definition_vec = get_definition_parts(&arg[..]);
-                let option = &option_definition_vec[0];
-                options.push(option.to_string());
-            } else {
-                options.push(arg.clone());
+/// The classification of a single command line argument in `Command.argv`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TokenKind {
+    /// The executable path at index position `0`
+    Executable,
+    /// A short option with a single option character (e.g., `-o`)
+    ShortFlag,
+    /// A multi-option short syntax (mops) style option (e.g., `-lmn`)
+    ShortCluster,
+    /// A short definition option (e.g., `-o=def`)
+    ShortWithValue,
+    /// A long option (e.g., `--output`)
+    LongFlag,
+    /// A long definition option (e.g., `--output=def`)
+    LongWithValue,
+    /// The single hyphen `-` idiom that is used to represent stdin/stdout
+    Stdin,
+    /// The double hyphen `--` idiom that ends option parsing
+    Terminator,
+    /// A positional argument that is not an option
+    Positional,
+    /// An option argument that is separated from the option that requires it (e.g., `path` in `-o path`)
+    OptionValue,
+    /// An argument that follows the end of option parsing at a double hyphen `--` idiom or, with `ParseOrder::RequireOrder`, at the first positional argument
+    TrailingArg,
+}
+
+/// The option parsing order, equivalent to the GNU getopt argument ordering modes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ParseOrder {
+    /// Options are parsed at any position before a double hyphen `--` idiom (GNU argument permutation)
+    #[default]
+    Permute,
+    /// Option parsing stops at the first positional argument (POSIX ordering, GNU `+` optstring prefix)
+    RequireOrder,
+    /// Options are parsed at any position and positional arguments are returned in order with the options (GNU `-` optstring prefix)
+    ReturnInOrder,
+}
+
+impl ParseOrder {
+    /// Returns `ParseOrder::RequireOrder` when the `POSIXLY_CORRECT` environment variable is defined and `ParseOrder::Permute` when it is not.
+    pub fn from_env() -> Self {
+        match std::env::var_os("POSIXLY_CORRECT") {
+            Some(_) => ParseOrder::RequireOrder,
+            None => ParseOrder::Permute,
+        }
+    }
+}
+
+/// The location of a command line argument, or of a part of an argument, in `Command.argv`
+///
+/// `start` and `end` define the byte range of the located text within the argument at index position `index`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Span {
+    /// The index position of the argument in `Command.argv`
+    pub index: usize,
+    /// The byte offset of the start of the located text in the argument
+    pub start: usize,
+    /// The byte offset of the end (exclusive) of the located text in the argument
+    pub end: usize,
+}
+
+/// A classified command line argument
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Token {
+    /// The classification of the argument
+    pub kind: TokenKind,
+    /// The index position of the argument in `Command.argv`
+    pub index: usize,
+    /// The argument string
+    pub text: String,
+}
+
+impl Token {
+    /// Returns boolean for the question "Is the token a short or long option?"
+    pub fn is_option(&self) -> bool {
+        matches!(
+            self.kind,
+            TokenKind::ShortFlag
+                | TokenKind::ShortCluster
+                | TokenKind::ShortWithValue
+                | TokenKind::LongFlag
+                | TokenKind::LongWithValue
+        )
+    }
+
+    /// Returns `Option<&str>` with the option part of an option token (i.e., before the equal symbol in a definition option).
+    /// Returns `None` if the token is not an option.
+    pub fn option(&self) -> Option<&str> {
+        match self.kind {
+            TokenKind::ShortWithValue | TokenKind::LongWithValue => self.text.split('=').next(),
+            _ if self.is_option() => Some(&self.text[..]),
+            _ => None,
+        }
+    }
+
+    /// Returns `Option<&str>` with the definition part of a definition option token (i.e., after the equal symbol).
+    /// Returns `None` if the token is not a definition option.
+    pub fn definition(&self) -> Option<&str> {
+        match self.kind {
+            TokenKind::ShortWithValue | TokenKind::LongWithValue => {
+                self.text.split_once('=').map(|parts| parts.1)
             }
+            _ => None,
+        }
+    }
+
+    /// Returns `Span` that locates the entire argument in `Command.argv`.
+    pub fn span(&self) -> Span {
+        Span {
+            index: self.index,
+            start: 0,
+            end: self.text.len(),
         }
     }
 
-    options
+    /// Returns `Option<Span>` that locates the option part of an option token.
+    /// Returns `None` if the token is not an option.
+    pub fn option_span(&self) -> Option<Span> {
+        self.option().map(|option| Span {
+            index: self.index,
+            start: 0,
+            end: option.len(),
+        })
+    }
+
+    /// Returns `Option<Span>` that locates the definition part of a definition option token.
+    /// Returns `None` if the token is not a definition option.
+    pub fn definition_span(&self) -> Option<Span> {
+        self.definition().map(|definition| Span {
+            index: self.index,
+            start: self.text.len() - definition.len(),
+            end: self.text.len(),
+        })
+    }
+
+    /// Returns `Vec<(String, Span)>` with each short option switch in a short option token formatted as `-[x]` and mapped to the location of the switch character (e.g., the `o` inside `-xvo`).
+    /// Returns an empty Vector if the token is not a short option.
+    pub fn short_options(&self) -> Vec<(String, Span)> {
+        match self.kind {
+            TokenKind::ShortFlag | TokenKind::ShortCluster | TokenKind::ShortWithValue => {}
+            _ => return Vec::new(),
+        }
+        let option = self.option().unwrap_or("");
+        option
+            .char_indices()
+            .skip(1) // the hyphen
+            .map(|(start, c)| {
+                (
+                    format!("-{}", c),
+                    Span {
+                        index: self.index,
+                        start,
+                        end: start + c.len_utf8(),
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+/// Returns `Vec<Token>` with one classified `Token` for each argument in `argv`.
+///
+/// # Remarks
+/// This is the single pass over the command line arguments that the `Command` struct fields are derived from.  Index position `0` is classified as the executable.  Per POSIX guidelines the single hyphen `-` is not an option and all arguments that follow a double hyphen `--` are not parsed as options.
+///
+/// Options are parsed with the `ParseOrder::Permute` order.  See `tokenize_with_order` for other parsing orders.
+pub fn tokenize(argv: &[String]) -> Vec<Token> {
+    tokenize_with_order(argv, ParseOrder::Permute)
+}
+
+/// Returns `Vec<Token>` with one classified `Token` for each argument in `argv` that is parsed with the option parsing order `order`.
+///
+/// # Remarks
+/// With `ParseOrder::RequireOrder`, the first positional argument and all arguments that follow it are classified as `TokenKind::TrailingArg`.  The `ParseOrder::Permute` and `ParseOrder::ReturnInOrder` orders classify arguments identically.
+///
+/// An option value in a separate argument is classified as `TokenKind::Positional` because the options that take a value are not known (e.g., `root` in `sudo -u root ls`).  Use `tokenize_with_values` to classify separate option values.
+pub fn tokenize_with_order(argv: &[String], order: ParseOrder) -> Vec<Token> {
+    tokenize_with_values(argv, order, |_| false)
+}
+
+/// Returns `Vec<Token>` with one classified `Token` for each argument in `argv` that is parsed with the option parsing order `order`.
+///
+/// # Remarks
+/// `consumes_value` answers the question "Does the option token require the argument that follows it as its option argument?".  The argument that follows an option token with a `true` answer is classified as `TokenKind::OptionValue` irrespective of its format, following the POSIX convention for separated option arguments.
+pub fn tokenize_with_values<F>(argv: &[String], order: ParseOrder, consumes_value: F) -> Vec<Token>
+where
+    F: Fn(&Token) -> bool,
+{
+    let mut tokens: Vec<Token> = Vec::with_capacity(argv.len());
+    let mut options_ended = false;
+    let mut value_expected = false;
+    for (index, arg) in argv.iter().enumerate() {
+        let kind = if index == 0 {
+            TokenKind::Executable
+        } else if options_ended {
+            TokenKind::TrailingArg
+        } else if value_expected {
+            TokenKind::OptionValue
+        } else {
+            classify(&arg[..])
+        };
+        let kind = if kind == TokenKind::Positional && order == ParseOrder::RequireOrder {
+            options_ended = true;
+            TokenKind::TrailingArg
+        } else {
+            kind
+        };
+        if kind == TokenKind::Terminator {
+            options_ended = true;
+        }
+        let token = Token {
+            kind,
+            index,
+            text: arg.clone(),
+        };
+        value_expected = token.is_option() && consumes_value(&token);
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+// Returns the `TokenKind` for an argument that is encountered while options are still parsed
+fn classify(arg: &str) -> TokenKind {
+    if arg == "-" {
+        TokenKind::Stdin
+    } else if is_double_hyphen_option(arg) {
+        TokenKind::Terminator
+    } else if arg.starts_with("--") {
+        if is_definition_option(arg) {
+            TokenKind::LongWithValue
+        } else {
+            TokenKind::LongFlag
+        }
+    } else if arg.starts_with('-') {
+        if is_definition_option(arg) {
+            TokenKind::ShortWithValue
+        } else if is_mops_option(arg) {
+            TokenKind::ShortCluster
+        } else {
+            TokenKind::ShortFlag
+        }
+    } else {
+        TokenKind::Positional
+    }
+}
+
+/// Returns `Vec<String>` of command line option arguments in a command line string.
+pub fn parse_options(argv: &[String]) -> Vec<String> {
+    options_from_tokens(&tokenize(argv))
+}
+
+/// Returns `Vec<String>` of command line option arguments in a token stream.
+pub fn options_from_tokens(tokens: &[Token]) -> Vec<String> {
+    tokens
+        .iter()
+        .filter_map(|token| token.option())
+        .map(String::from)
+        .collect()
 }
 
 /// Returns `std::collections::HashMap<String, String>` with key:value mapped as option:definition.
 pub fn parse_definitions(argv: &[String]) -> HashMap<String, String> {
+    definitions_from_tokens(&tokenize(argv))
+}
+
+/// Returns `std::collections::HashMap<String, String>` with key:value mapped as option:definition in a token stream.
+pub fn definitions_from_tokens(tokens: &[Token]) -> HashMap<String, String> {
     let mut definitions: HashMap<String, String> = HashMap::new();
-    for arg in argv {
-        if arg.starts_with('-') {
-            // test to confirm that we haven't encountered a double
-            // hyphen command line option as this indicates that all
-            // subsequent argument parsing for options should be ignored
-            if is_double_hyphen_option(&arg[..]) {
-                break;
-            }
-            // test for a definition formatted option (e.g., `--option=definition`)
-            // parse to option and definition parts if identified
-            if is_definition_option(&arg[..]) {
-                let option_definition_vec = get_definition_parts(&arg[..]);
-                let option = &option_definition_vec[0];
-                let definition = &option_definition_vec[1];
-                definitions.insert(option.to_string(), definition.to_string());
-            }
+    for token in tokens {
+        if let (Some(option), Some(definition)) = (token.option(), token.definition()) {
+            definitions.insert(option.to_string(), definition.to_string());
         }
     }
 
@@ -66,40 +298,74 @@ pub fn parse_definitions(argv: &[String]) -> HashMap<String, String> {
 /// Returns `Option<String>` with the first positional argument to the executable.
 /// Returns `None` if the command was entered as the executable only.
 pub fn parse_first_arg(arg_list: &[String]) -> Option<String> {
-    match arg_list.get(1) {
-        Some(x) => Some(x.clone()),
-        None => None,
-    }
+    first_arg_from_tokens(&tokenize(arg_list))
+}
+
+/// Returns `Option<String>` with the first argument to the executable in a token stream.
+/// Returns `None` if the command was entered as the executable only.
+pub fn first_arg_from_tokens(tokens: &[Token]) -> Option<String> {
+    tokens
+        .iter()
+        .find(|token| token.kind != TokenKind::Executable)
+        .map(|token| token.text.clone())
 }
 
 /// Returns `Option<String>` with the last positional argument to the executable.
 /// Returns `None` if the command was entered as the executable only.
 pub fn parse_last_arg(arg_list: &[String]) -> Option<String> {
-    if arg_list.len() > 1 {
-        match arg_list.get(arg_list.len() - 1) {
-            Some(x) => Some(x.clone()),
-            None => None,
-        }
-    } else {
-        None // return None if this is an executable only (e.g. only includes index position 0 with length = 1) command
-    }
+    last_arg_from_tokens(&tokenize(arg_list))
+}
+
+/// Returns `Option<String>` with the last argument to the executable in a token stream.
+/// Returns `None` if the command was entered as the executable only.
+pub fn last_arg_from_tokens(tokens: &[Token]) -> Option<String> {
+    tokens
+        .iter()
+        .rev()
+        .find(|token| token.kind != TokenKind::Executable)
+        .map(|token| token.text.clone())
 }
 
 /// Returns `Options<Vec<String>>` with Vector of arguments following a double hyphen `--` command line argument idiom.
 /// Returns `None` if there was no double hyphen idiom present or there are no arguments following the double hyphen argument.
 pub fn parse_double_hyphen_args(arg_list: &[String]) -> Option<Vec<String>> {
-    for (index, value) in arg_list.iter().enumerate() {
-        if is_double_hyphen_option(&value[..]) {
-            let sub_vec = arg_list[(index + 1)..arg_list.len()].to_vec();
-            if !sub_vec.is_empty() {
-                return Some(sub_vec);
-            } else {
-                return None;
-            }
-        }
+    double_hyphen_args_from_tokens(&tokenize(arg_list))
+}
+
+/// Returns `Options<Vec<String>>` with Vector of arguments following a double hyphen `--` command line argument idiom in a token stream.
+/// Returns `None` if there was no double hyphen idiom present or there are no arguments following the double hyphen argument.
+pub fn double_hyphen_args_from_tokens(tokens: &[Token]) -> Option<Vec<String>> {
+    let trailing: Vec<String> = tokens
+        .iter()
+        .skip_while(|token| token.kind != TokenKind::Terminator)
+        .filter(|token| token.kind == TokenKind::TrailingArg)
+        .map(|token| token.text.clone())
+        .collect();
+
+    if trailing.is_empty() {
+        None
+    } else {
+        Some(trailing)
     }
+}
 
-    None
+/// Returns `Options<Vec<String>>` with Vector of arguments that follow the end of option parsing in a token stream.
+/// Returns `None` if option parsing did not end before the last argument.
+///
+/// # Remarks
+/// Option parsing ends at a double hyphen `--` idiom, which is not included in the Vector, or, with `ParseOrder::RequireOrder`, at the first positional argument, which is included in the Vector.
+pub fn remainder_args_from_tokens(tokens: &[Token]) -> Option<Vec<String>> {
+    let remainder: Vec<String> = tokens
+        .iter()
+        .filter(|token| token.kind == TokenKind::TrailingArg)
+        .map(|token| token.text.clone())
+        .collect();
+
+    if remainder.is_empty() {
+        None
+    } else {
+        Some(remainder)
+    }
 }
 
 /// Returns `Option<Vec<String>>` that includes unique short options parsed from the command arguments, including any multi-option short syntax options.
@@ -134,22 +400,19 @@ pub fn parse_mops(arg_list: &[String]) -> Option<Vec<String>> {
     }
 }
 
+/// Returns `usize` with the index position of the last option in the command line string.
+/// Returns `0` if there are no options in the command.
 pub fn parse_loptind_index(arg_list: &[String]) -> usize {
-    let mut counter = 0;
-    for (index, value) in arg_list.iter().enumerate() {
-        if value.starts_with('-') {
-            if is_double_hyphen_option(value) {
-                // if double hyphen idiom is identified do not set index to it and return the counter
-                return counter;
-            } else if value == "-" {
-                // ignore single hyphen idiom, it is not an option
-                continue;
-            }
-            counter = index;
-        }
-    }
+    loptind_index_from_tokens(&tokenize(arg_list))
+}
 
-    counter
+/// Returns `usize` with the index position of the last option in a token stream.
+/// Returns `0` if there are no options in the token stream.
+pub fn loptind_index_from_tokens(tokens: &[Token]) -> usize {
+    match tokens.iter().rev().find(|token| token.is_option()) {
+        Some(token) => token.index,
+        None => 0,
+    }
 }
 
 /// Returns boolean for the question "Is `needle` a definition option?".
@@ -172,7 +435,7 @@ pub fn is_double_hyphen_option(needle: &str) -> bool {
 /// Returns boolean for the question "Is `needle` a multi-option short syntax (mops) style option argument?"
 pub fn is_mops_option(needle: &str) -> bool {
     // must have single hyphen syntax with more than one option character
-    needle.starts_with('-') && !needle.starts_with("--") && needle.len() > 2
+    needle.starts_with('-') && !needle.starts_with("--") && needle.chars().count() > 2
 }
 
 /// Returns `Vec<String>` of definition option parts with two index positions.
@@ -181,7 +444,7 @@ pub fn is_mops_option(needle: &str) -> bool {
 /// * index position `0`: option argument String (i.e., before the equal symbol)
 /// * index position `1`: definition argument String (i.e., after the equal symbol)
 pub fn get_definition_parts(needle: &str) -> Vec<String> {
-    let opt_def: Vec<_> = needle.split('=').collect();
+    let opt_def: Vec<_> = needle.splitn(2, '=').collect();
     vec![String::from(opt_def[0]), String::from(opt_def[1])]
 }
 
@@ -190,6 +453,257 @@ pub fn get_definition_parts(needle: &str) -> Vec<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn function_tokenize() {
+        let test_vec = vec![
+            String::from("tester"),
+            String::from("subcommand"),
+            String::from("-o"),
+            String::from("-lmn"),
+            String::from("-d=def"),
+            String::from("--longoption"),
+            String::from("--defoption=equaldefinition"),
+            String::from("-"),
+            String::from("--"),
+            String::from("--afterdoublehyphen"),
+            String::from("--"),
+        ];
+
+        let expected_kinds = vec![
+            TokenKind::Executable,
+            TokenKind::Positional,
+            TokenKind::ShortFlag,
+            TokenKind::ShortCluster,
+            TokenKind::ShortWithValue,
+            TokenKind::LongFlag,
+            TokenKind::LongWithValue,
+            TokenKind::Stdin,
+            TokenKind::Terminator,
+            TokenKind::TrailingArg,
+            TokenKind::TrailingArg, // only the first double hyphen is a terminator
+        ];
+
+        let tokens = tokenize(&test_vec);
+        let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, expected_kinds);
+        for (index, token) in tokens.iter().enumerate() {
+            assert_eq!(token.index, index);
+            assert_eq!(token.text, test_vec[index]);
+        }
+    }
+
+    #[test]
+    fn function_tokenize_with_order_require_order() {
+        let test_vec = vec![
+            String::from("sudo"),
+            String::from("-u"),
+            String::from("root"),
+            String::from("ls"),
+            String::from("-l"),
+            String::from("--"),
+            String::from("-a"),
+        ];
+
+        let expected_kinds = vec![
+            TokenKind::Executable,
+            TokenKind::ShortFlag,
+            TokenKind::TrailingArg, // option parsing stops at the first positional
+            TokenKind::TrailingArg,
+            TokenKind::TrailingArg,
+            TokenKind::TrailingArg,
+            TokenKind::TrailingArg,
+        ];
+
+        let tokens = tokenize_with_order(&test_vec, ParseOrder::RequireOrder);
+        let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, expected_kinds);
+        assert_eq!(double_hyphen_args_from_tokens(&tokens), None);
+        assert_eq!(
+            remainder_args_from_tokens(&tokens),
+            Some(test_vec[2..].to_vec())
+        );
+    }
+
+    #[test]
+    fn function_tokenize_with_order_permute_and_return_in_order() {
+        let test_vec = vec![
+            String::from("env"),
+            String::from("pos"),
+            String::from("-l"),
+            String::from("--"),
+            String::from("-a"),
+        ];
+
+        let permute = tokenize_with_order(&test_vec, ParseOrder::Permute);
+        let in_order = tokenize_with_order(&test_vec, ParseOrder::ReturnInOrder);
+        assert_eq!(permute, tokenize(&test_vec));
+        assert_eq!(permute, in_order);
+        assert_eq!(permute[2].kind, TokenKind::ShortFlag);
+        assert_eq!(
+            double_hyphen_args_from_tokens(&permute),
+            remainder_args_from_tokens(&permute)
+        );
+    }
+
+    #[test]
+    fn function_tokenize_with_values() {
+        let test_vec = vec![
+            String::from("tester"),
+            String::from("-o"),
+            String::from("-x"),
+            String::from("--output"),
+            String::from("--"),
+            String::from("pos"),
+            String::from("-o"),
+        ];
+        let tokens = tokenize_with_values(&test_vec, ParseOrder::RequireOrder, |token| {
+            token.option() == Some("-o") || token.option() == Some("--output")
+        });
+        let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Executable,
+                TokenKind::ShortFlag,
+                TokenKind::OptionValue, // option arguments may begin with a hyphen
+                TokenKind::LongFlag,
+                TokenKind::OptionValue, // option arguments may be a double hyphen
+                TokenKind::TrailingArg, // option value is not the first positional
+                TokenKind::TrailingArg,
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_order_default() {
+        assert_eq!(ParseOrder::default(), ParseOrder::Permute);
+    }
+
+    #[test]
+    fn function_tokenize_executable_only() {
+        let test_vec = vec![String::from("--tester")]; // index 0 is always the executable
+        let tokens = tokenize(&test_vec);
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].kind, TokenKind::Executable);
+        assert_eq!(tokens[0].option(), None);
+    }
+
+    #[test]
+    fn token_option_and_definition() {
+        let tokens = tokenize(&[
+            String::from("tester"),
+            String::from("--opt=a=b"),
+            String::from("-o=def"),
+            String::from("-lmn"),
+            String::from("-"),
+            String::from("pos"),
+        ]);
+
+        assert_eq!(tokens[1].option(), Some("--opt"));
+        assert_eq!(tokens[1].definition(), Some("a=b")); // splits at the first equal symbol only
+        assert_eq!(tokens[2].option(), Some("-o"));
+        assert_eq!(tokens[2].definition(), Some("def"));
+        assert_eq!(tokens[3].option(), Some("-lmn"));
+        assert_eq!(tokens[3].definition(), None);
+        assert_eq!(tokens[4].option(), None); // single hyphen is not an option
+        assert_eq!(tokens[5].option(), None);
+    }
+
+    #[test]
+    fn token_spans() {
+        let tokens = tokenize(&[
+            String::from("tester"),
+            String::from("-xvo"),
+            String::from("--opt=def"),
+            String::from("-o"),
+            String::from("pos"),
+        ]);
+
+        assert_eq!(
+            tokens[1].span(),
+            Span {
+                index: 1,
+                start: 0,
+                end: 4
+            }
+        );
+        assert_eq!(
+            tokens[1].short_options(),
+            vec![
+                (
+                    String::from("-x"),
+                    Span {
+                        index: 1,
+                        start: 1,
+                        end: 2
+                    }
+                ),
+                (
+                    String::from("-v"),
+                    Span {
+                        index: 1,
+                        start: 2,
+                        end: 3
+                    }
+                ),
+                (
+                    String::from("-o"),
+                    Span {
+                        index: 1,
+                        start: 3,
+                        end: 4
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            tokens[2].option_span(),
+            Some(Span {
+                index: 2,
+                start: 0,
+                end: 5
+            })
+        );
+        assert_eq!(
+            tokens[2].definition_span(),
+            Some(Span {
+                index: 2,
+                start: 6,
+                end: 9
+            })
+        );
+        assert_eq!(tokens[2].short_options(), vec![]); // long options have no short switches
+        assert_eq!(
+            tokens[3].short_options(),
+            vec![(
+                String::from("-o"),
+                Span {
+                    index: 3,
+                    start: 1,
+                    end: 2
+                }
+            )]
+        );
+        assert_eq!(tokens[4].option_span(), None);
+        assert_eq!(tokens[4].definition_span(), None);
+    }
+
+    #[test]
+    fn function_parse_definitions_skips_single_hyphen_and_trailing_args() {
+        let test_vec = vec![
+            String::from("tester"),
+            String::from("-"),
+            String::from("--key=a=b"),
+            String::from("--"),
+            String::from("--absent=true"),
+        ];
+
+        let mut expected_hm = HashMap::new();
+        expected_hm.insert("--key".to_string(), "a=b".to_string());
+
+        assert_eq!(parse_definitions(&test_vec), expected_hm);
+    }
+
     #[test]
     fn function_parse_options() {
         let test_vec = vec![
@@ -0,0 +1,540 @@
+// Copyright 2018 Christopher Simpkins
+// Licensed under the MIT license
+
+//! Byte size, duration, percentage, and rate value parsing support
+//!
+//! The parsers are also available as `FromStr` types (`ByteSize`, `HumanDuration`, `Percentage`, and `Rate`) that can be used with `spec::OptionSpec::value_type`, `spec::Value::parse`, and `str::parse` on the definitions returned by `Command::get_definition_for`.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A value parsing error
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValueError {
+    /// The value is empty
+    Empty,
+    /// The value does not include a valid number.  The field is the value.
+    InvalidNumber(String),
+    /// The value includes a unit that is missing or is not valid.  The fields are the value and the unit.
+    InvalidUnit(String, String),
+    /// The value is too large to be represented.  The field is the value.
+    OutOfRange(String),
+}
+
+// Display trait
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValueError::Empty => write!(f, "empty value"),
+            ValueError::InvalidNumber(value) => write!(f, "invalid number in '{}'", value),
+            ValueError::InvalidUnit(value, unit) if unit.is_empty() => {
+                write!(f, "missing unit in '{}'", value)
+            }
+            ValueError::InvalidUnit(value, unit) => {
+                write!(f, "invalid unit '{}' in '{}'", unit, value)
+            }
+            ValueError::OutOfRange(value) => write!(f, "value '{}' is out of range", value),
+        }
+    }
+}
+
+// Error trait
+impl Error for ValueError {}
+
+/// Returns `Result<u64, ValueError>` with the number of bytes in the byte size `value` (e.g., `512MiB`, `1.5 GB`, `64k`)
+///
+/// # Remarks
+/// Units are case insensitive.  SI units (`kB`, `MB`, `GB`, `TB`, `PB`, `EB`) are powers of 1000 and IEC units (`KiB`, `MiB`, `GiB`, `TiB`, `PiB`, `EiB`) are powers of 1024.  Following the GNU coreutils convention, single letter units (`K`, `M`, `G`, `T`, `P`, `E`) are powers of 1024.  A value without a unit or with the `B` unit is a number of bytes.  Fractional values are rounded to the nearest byte.
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::parsers::values::parse_size;
+///
+/// assert_eq!(parse_size("512MiB"), Ok(536_870_912));
+/// assert_eq!(parse_size("1.5 kB"), Ok(1_500));
+/// assert!(parse_size("12 parsecs").is_err());
+/// ```
+pub fn parse_size(value: &str) -> Result<u64, ValueError> {
+    let (number, unit) = split_unit(value)?;
+    let (whole, fraction) = split_decimal(value, number)?;
+    let multiplier: u64 = match &unit.to_lowercase()[..] {
+        "" | "b" => 1,
+        "kb" => 1_000,
+        "mb" => 1_000_000,
+        "gb" => 1_000_000_000,
+        "tb" => 1_000_000_000_000,
+        "pb" => 1_000_000_000_000_000,
+        "eb" => 1_000_000_000_000_000_000,
+        "k" | "kib" => 1 << 10,
+        "m" | "mib" => 1 << 20,
+        "g" | "gib" => 1 << 30,
+        "t" | "tib" => 1 << 40,
+        "p" | "pib" => 1 << 50,
+        "e" | "eib" => 1 << 60,
+        _ => return Err(ValueError::InvalidUnit(value.to_string(), unit.to_string())),
+    };
+    // the fraction of the multiplier is less than the multiplier
+    let fraction = (fraction * multiplier as f64).round() as u64;
+    whole
+        .checked_mul(multiplier)
+        .and_then(|x| x.checked_add(fraction))
+        .ok_or_else(|| ValueError::OutOfRange(value.to_string()))
+}
+
+/// Returns `Result<Duration, ValueError>` with the duration in the human readable duration `value` (e.g., `1m30s`, `1.5h`, `250ms`)
+///
+/// # Remarks
+/// A duration is a sequence of numbers with units.  The units are `ns`, `us` (or `µs`), `ms`, `s`, `m` (or `min`), `h`, and `d`.  A unit is required, with the exception of the `0` duration.
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::parsers::values::parse_duration;
+/// use std::time::Duration;
+///
+/// assert_eq!(parse_duration("1m30s"), Ok(Duration::from_secs(90)));
+/// assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+/// assert!(parse_duration("90").is_err());
+/// ```
+pub fn parse_duration(value: &str) -> Result<Duration, ValueError> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Err(ValueError::Empty);
+    }
+    if trimmed == "0" {
+        return Ok(Duration::from_secs(0));
+    }
+    let out_of_range = || ValueError::OutOfRange(value.to_string());
+    let mut nanos: u128 = 0;
+    let mut rest = trimmed;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let unit_end = rest[number_end..]
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .map_or(rest.len(), |x| number_end + x);
+        let (whole, fraction) = split_decimal(value, &rest[..number_end])?;
+        let unit = rest[number_end..unit_end].trim();
+        let multiplier: u128 = match unit {
+            "ns" => 1,
+            "us" | "µs" => 1_000,
+            "ms" => 1_000_000,
+            "s" => 1_000_000_000,
+            "m" | "min" => 60_000_000_000,
+            "h" => 3_600_000_000_000,
+            "d" => 86_400_000_000_000,
+            _ => return Err(ValueError::InvalidUnit(value.to_string(), unit.to_string())),
+        };
+        // the fraction of the multiplier is less than the multiplier
+        let fraction = (fraction * multiplier as f64).round() as u128;
+        nanos = (whole as u128)
+            .checked_mul(multiplier)
+            .and_then(|x| x.checked_add(fraction))
+            .and_then(|x| x.checked_add(nanos))
+            .ok_or_else(out_of_range)?;
+        rest = &rest[unit_end..];
+    }
+    let secs = u64::try_from(nanos / 1_000_000_000).map_err(|_| out_of_range())?;
+    Ok(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+}
+
+/// Returns `Result<f64, ValueError>` with the fraction in the percentage `value` (e.g., `0.5` for `50%`)
+///
+/// # Remarks
+/// The percent sign is optional.
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::parsers::values::parse_percentage;
+///
+/// assert_eq!(parse_percentage("50%"), Ok(0.5));
+/// assert_eq!(parse_percentage("12.5"), Ok(0.125));
+/// ```
+pub fn parse_percentage(value: &str) -> Result<f64, ValueError> {
+    let (number, unit) = split_number(value)?;
+    match unit {
+        "" | "%" => scale(value, number, 0.01),
+        _ => Err(ValueError::InvalidUnit(value.to_string(), unit.to_string())),
+    }
+}
+
+/// Returns `Result<Rate, ValueError>` with the rate in the rate `value` (e.g., `10/s`, `100/5m`)
+///
+/// # Remarks
+/// A rate is a number of events, a slash, and a duration in the `parse_duration` format.  The number of the duration is optional (e.g., `s` is `1s`).
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::parsers::values::parse_rate;
+///
+/// assert_eq!(parse_rate("10/s").unwrap().per_second(), 10.0);
+/// assert_eq!(parse_rate("30/1m").unwrap().per_second(), 0.5);
+/// ```
+pub fn parse_rate(value: &str) -> Result<Rate, ValueError> {
+    let (count, per) = match value.trim().split_once('/') {
+        Some(x) => x,
+        None if value.trim().is_empty() => return Err(ValueError::Empty),
+        None => return Err(ValueError::InvalidUnit(value.to_string(), String::new())),
+    };
+    let count = parse_number(value, count.trim())?;
+    let per = per.trim();
+    let per = if per.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        parse_duration(per)
+    } else {
+        parse_duration(&format!("1{}", per))
+    }
+    .map_err(|e| match e {
+        ValueError::InvalidUnit(_, unit) => ValueError::InvalidUnit(value.to_string(), unit),
+        _ => ValueError::InvalidNumber(value.to_string()),
+    })?;
+    if per == Duration::from_secs(0) {
+        return Err(ValueError::OutOfRange(value.to_string()));
+    }
+    Ok(Rate { count, per })
+}
+
+// Splits `value` into the number and the unit that follows it
+fn split_number(value: &str) -> Result<(f64, &str), ValueError> {
+    let (number, unit) = split_unit(value)?;
+    Ok((parse_number(value, number)?, unit))
+}
+
+// Splits `value` into the text of the number and the unit that follows it
+fn split_unit(value: &str) -> Result<(&str, &str), ValueError> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Err(ValueError::Empty);
+    }
+    let end = trimmed
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(trimmed.len());
+    Ok((&trimmed[..end], trimmed[end..].trim_start()))
+}
+
+// Splits the non-negative decimal number `number` in `value` into the whole number and the fraction,
+// or returns an out of range error when the whole number is larger than `u64::MAX`
+fn split_decimal(value: &str, number: &str) -> Result<(u64, f64), ValueError> {
+    let invalid = || ValueError::InvalidNumber(value.to_string());
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let whole = match whole {
+        "" => 0,
+        x => x
+            .parse::<u64>()
+            .map_err(|_| ValueError::OutOfRange(value.to_string()))?,
+    };
+    let fraction = match fraction {
+        "" => 0.0,
+        x => format!("0.{}", x).parse::<f64>().map_err(|_| invalid())?,
+    };
+    Ok((whole, fraction))
+}
+
+// Parses the non-negative decimal number `number` in `value`
+fn parse_number(value: &str, number: &str) -> Result<f64, ValueError> {
+    match number.parse::<f64>() {
+        Ok(x) if !number.is_empty() && x.is_finite() => Ok(x),
+        _ => Err(ValueError::InvalidNumber(value.to_string())),
+    }
+}
+
+// Multiplies `number` in `value` by `multiplier`
+fn scale(value: &str, number: f64, multiplier: f64) -> Result<f64, ValueError> {
+    let scaled = number * multiplier;
+    if scaled.is_finite() {
+        Ok(scaled)
+    } else {
+        Err(ValueError::OutOfRange(value.to_string()))
+    }
+}
+
+/// A byte size option value (e.g., `512MiB`) that is parsed with `parse_size`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ByteSize(pub u64);
+
+// FromStr trait
+impl FromStr for ByteSize {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_size(s).map(ByteSize)
+    }
+}
+
+/// A duration option value (e.g., `1m30s`) that is parsed with `parse_duration`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HumanDuration(pub Duration);
+
+// FromStr trait
+impl FromStr for HumanDuration {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_duration(s).map(HumanDuration)
+    }
+}
+
+/// A percentage option value (e.g., `50%`) that is parsed with `parse_percentage` as a fraction
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Percentage(pub f64);
+
+// FromStr trait
+impl FromStr for Percentage {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_percentage(s).map(Percentage)
+    }
+}
+
+/// A rate option value (e.g., `10/s`) that is parsed with `parse_rate`
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rate {
+    /// The number of events
+    pub count: f64,
+    /// The duration of the events
+    pub per: Duration,
+}
+
+impl Rate {
+    /// Returns `f64` with the number of events per second
+    pub fn per_second(&self) -> f64 {
+        self.count / self.per.as_secs_f64()
+    }
+}
+
+// FromStr trait
+impl FromStr for Rate {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_rate(s)
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn function_parse_size() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("512B"), Ok(512));
+        assert_eq!(parse_size("512MiB"), Ok(512 * 1024 * 1024));
+        assert_eq!(parse_size("512mib"), Ok(512 * 1024 * 1024));
+        assert_eq!(parse_size("2 GB"), Ok(2_000_000_000));
+        assert_eq!(parse_size("64k"), Ok(65_536));
+        assert_eq!(parse_size("1.5KiB"), Ok(1_536));
+        assert_eq!(parse_size("1.5"), Ok(2));
+        assert_eq!(parse_size(".5k"), Ok(512));
+        assert_eq!(parse_size("9007199254740993"), Ok(9_007_199_254_740_993));
+        assert_eq!(parse_size("18446744073709551615"), Ok(u64::MAX));
+        assert_eq!(
+            parse_size("18446744073709551000"),
+            Ok(18_446_744_073_709_551_000)
+        );
+        assert_eq!(
+            parse_size("18446744073709551616"),
+            Err(ValueError::OutOfRange("18446744073709551616".to_string()))
+        );
+        assert_eq!(
+            parse_size("18446744073709551615.5"),
+            Err(ValueError::OutOfRange("18446744073709551615.5".to_string()))
+        );
+        assert_eq!(parse_size("15.5EiB"), Ok(17_870_283_321_406_128_128));
+        assert_eq!(
+            parse_size("16EiB"),
+            Err(ValueError::OutOfRange("16EiB".to_string()))
+        );
+        assert_eq!(parse_size(""), Err(ValueError::Empty));
+        assert_eq!(
+            parse_size("-1"),
+            Err(ValueError::InvalidNumber("-1".to_string()))
+        );
+        assert_eq!(
+            parse_size("MiB"),
+            Err(ValueError::InvalidNumber("MiB".to_string()))
+        );
+        assert_eq!(
+            parse_size("."),
+            Err(ValueError::InvalidNumber(".".to_string()))
+        );
+        assert_eq!(
+            parse_size("1.2.3MiB"),
+            Err(ValueError::InvalidNumber("1.2.3MiB".to_string()))
+        );
+        assert_eq!(
+            parse_size("5XB"),
+            Err(ValueError::InvalidUnit("5XB".to_string(), "XB".to_string()))
+        );
+    }
+
+    #[test]
+    fn function_parse_duration() {
+        assert_eq!(parse_duration("0"), Ok(Duration::from_secs(0)));
+        assert_eq!(parse_duration("1m30s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("1h 2m 3s"), Ok(Duration::from_secs(3_723)));
+        assert_eq!(parse_duration("2d"), Ok(Duration::from_secs(172_800)));
+        assert_eq!(parse_duration("1min"), Ok(Duration::from_secs(60)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1_500)));
+        assert_eq!(parse_duration("10us"), Ok(Duration::from_micros(10)));
+        assert_eq!(parse_duration("10µs"), Ok(Duration::from_micros(10)));
+        assert_eq!(parse_duration("7ns"), Ok(Duration::from_nanos(7)));
+        assert_eq!(
+            parse_duration("100000000s 1ns"),
+            Ok(Duration::new(100_000_000, 1))
+        );
+        assert_eq!(
+            parse_duration("18446744073709551615s 999999999ns"),
+            Ok(Duration::new(u64::MAX, 999_999_999))
+        );
+        assert_eq!(
+            parse_duration("18446744073709551616s"),
+            Err(ValueError::OutOfRange("18446744073709551616s".to_string()))
+        );
+        assert_eq!(
+            parse_duration("18446744073709551615s 1s"),
+            Err(ValueError::OutOfRange(
+                "18446744073709551615s 1s".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_duration("1.2.3s"),
+            Err(ValueError::InvalidNumber("1.2.3s".to_string()))
+        );
+        assert_eq!(parse_duration(" "), Err(ValueError::Empty));
+        assert_eq!(
+            parse_duration("90"),
+            Err(ValueError::InvalidUnit("90".to_string(), "".to_string()))
+        );
+        assert_eq!(
+            parse_duration("1m30x"),
+            Err(ValueError::InvalidUnit(
+                "1m30x".to_string(),
+                "x".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_duration("m"),
+            Err(ValueError::InvalidNumber("m".to_string()))
+        );
+        assert_eq!(
+            format!("{}", parse_duration("90").unwrap_err()),
+            "missing unit in '90'"
+        );
+    }
+
+    #[test]
+    fn function_parse_percentage() {
+        assert_eq!(parse_percentage("50%"), Ok(0.5));
+        assert_eq!(parse_percentage("150 %"), Ok(1.5));
+        assert_eq!(parse_percentage("12.5"), Ok(0.125));
+        assert_eq!(
+            parse_percentage("50 percent"),
+            Err(ValueError::InvalidUnit(
+                "50 percent".to_string(),
+                "percent".to_string()
+            ))
+        );
+        assert_eq!(
+            format!("{}", parse_percentage("%").unwrap_err()),
+            "invalid number in '%'"
+        );
+    }
+
+    #[test]
+    fn function_parse_rate() {
+        let r1 = parse_rate("10/s").unwrap();
+        let r2 = parse_rate("100 / 5m").unwrap();
+        let r3 = parse_rate("1.5/ms").unwrap();
+
+        assert_eq!(r1.count, 10.0);
+        assert_eq!(r1.per, Duration::from_secs(1));
+        assert_eq!(r1.per_second(), 10.0);
+        assert_eq!(r2.per, Duration::from_secs(300));
+        assert_eq!(r3.per_second(), 1_500.0);
+        assert_eq!(parse_rate(""), Err(ValueError::Empty));
+        assert_eq!(
+            parse_rate("10"),
+            Err(ValueError::InvalidUnit("10".to_string(), "".to_string()))
+        );
+        assert_eq!(
+            parse_rate("10/fortnight"),
+            Err(ValueError::InvalidUnit(
+                "10/fortnight".to_string(),
+                "fortnight".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_rate("x/s"),
+            Err(ValueError::InvalidNumber("x/s".to_string()))
+        );
+        assert_eq!(
+            parse_rate("10/0s"),
+            Err(ValueError::OutOfRange("10/0s".to_string()))
+        );
+    }
+
+    #[test]
+    fn value_types_with_spec() {
+        use spec::{OptionSpec, Spec};
+
+        let spec = Spec::new("test")
+            .option(OptionSpec::value("--max-size").value_type::<ByteSize>())
+            .option(OptionSpec::value("--timeout").value_type::<HumanDuration>());
+        let argv: Vec<String> = vec!["test", "--max-size=512MiB", "--timeout", "1m30s"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let c = spec.parse(&argv).unwrap();
+        assert_eq!(
+            c.get_value("--max-size").and_then(|x| x.parse()),
+            Some(ByteSize(536_870_912))
+        );
+        assert_eq!(
+            c.get_value("--timeout").and_then(|x| x.parse()),
+            Some(HumanDuration(Duration::from_secs(90)))
+        );
+
+        let argv: Vec<String> = vec!["test", "--max-size=5XB"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            format!("{}", spec.parse(&argv).unwrap_err()),
+            "invalid value '5XB' for option '--max-size': invalid unit 'XB' in '5XB'"
+        );
+    }
+
+    #[test]
+    fn value_types_from_str() {
+        assert_eq!("1KiB".parse::<ByteSize>(), Ok(ByteSize(1_024)));
+        assert_eq!(
+            "1m".parse::<HumanDuration>(),
+            Ok(HumanDuration(Duration::from_secs(60)))
+        );
+        assert_eq!("25%".parse::<Percentage>(), Ok(Percentage(0.25)));
+        assert_eq!("60/m".parse::<Rate>().unwrap().per_second(), 1.0);
+        assert!("x".parse::<ByteSize>().is_err());
+    }
+}
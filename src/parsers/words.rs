@@ -0,0 +1,205 @@
+// Copyright 2018 Christopher Simpkins
+// Licensed under the MIT license
+
+//! Shell word splitting of command line strings
+
+/// A shell word of a command line string with its location in the string
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Word {
+    /// The word without the quotes and the escape characters
+    pub text: String,
+    /// The byte offset of the start of the word in the command line string
+    pub start: usize,
+    /// The byte offset of the end of the word (exclusive) in the command line string
+    pub end: usize,
+}
+
+// The quoting state at a character of a command line string
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Quote {
+    None,
+    Single,
+    Double,
+    Escape,
+    DoubleEscape,
+}
+
+/// Returns `Result<Vec<String>, String>` with the words of the command line string `line` that are split with shell quoting rules, or a failure message for an unterminated quote or escape
+///
+/// # Remarks
+/// Words are delimited by whitespace.  Single quotes preserve all of the quoted characters.  Double quotes preserve the quoted characters except for a backslash that escapes a double quote, a backslash, a dollar sign, or a backtick.  A backslash outside of quotes escapes the next character.  Variable expansion, globbing, and comments are not supported.
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::parsers::split_words;
+///
+/// assert_eq!(
+///     split_words(r#"get 'a b' "c \"d\"" e\ f"#),
+///     Ok(vec![
+///         String::from("get"),
+///         String::from("a b"),
+///         String::from("c \"d\""),
+///         String::from("e f")
+///     ])
+/// );
+/// assert!(split_words("get 'a b").is_err());
+/// ```
+pub fn split_words(line: &str) -> Result<Vec<String>, String> {
+    split_word_spans(line).map(|words| words.into_iter().map(|x| x.text).collect())
+}
+
+/// Returns `Result<Vec<Word>, String>` with the words of the command line string `line` and their locations, or a failure message for an unterminated quote or escape
+///
+/// # Remarks
+/// The quoting rules are the same as in `split_words`.
+pub fn split_word_spans(line: &str) -> Result<Vec<Word>, String> {
+    let (words, quote) = lex(line);
+    match quote {
+        Quote::None => Ok(words),
+        Quote::Single => Err(String::from("unterminated single quote")),
+        Quote::Double | Quote::DoubleEscape => Err(String::from("unterminated double quote")),
+        Quote::Escape => Err(String::from("unterminated escape")),
+    }
+}
+
+/// Returns `Vec<Word>` with the words of the command line string `line` and their locations, including a last word with an unterminated quote or escape
+///
+/// # Remarks
+/// Use this function to split an incomplete command line string (e.g., the text before the cursor for command line completion).
+pub fn split_words_partial(line: &str) -> Vec<Word> {
+    lex(line).0
+}
+
+// Returns the words of `line` and the quoting state at the end of `line`
+fn lex(line: &str) -> (Vec<Word>, Quote) {
+    let mut words: Vec<Word> = Vec::new();
+    let mut current: Option<Word> = None;
+    let mut quote = Quote::None;
+    for (i, c) in line.char_indices() {
+        if quote == Quote::None && c.is_whitespace() {
+            if let Some(word) = current.take() {
+                words.push(word);
+            }
+            continue;
+        }
+        let word = current.get_or_insert(Word {
+            text: String::new(),
+            start: i,
+            end: i,
+        });
+        word.end = i + c.len_utf8();
+        quote = match (quote, c) {
+            (Quote::None, '\'') => Quote::Single,
+            (Quote::None, '"') => Quote::Double,
+            (Quote::None, '\\') => Quote::Escape,
+            (Quote::Single, '\'') | (Quote::Double, '"') => Quote::None,
+            (Quote::Double, '\\') => Quote::DoubleEscape,
+            (Quote::Escape, _) => {
+                word.text.push(c);
+                Quote::None
+            }
+            (Quote::DoubleEscape, _) => {
+                if !matches!(c, '"' | '\\' | '$' | '`') {
+                    word.text.push('\\');
+                }
+                word.text.push(c);
+                Quote::Double
+            }
+            (_, _) => {
+                word.text.push(c);
+                quote
+            }
+        };
+    }
+    if let Some(word) = current {
+        words.push(word);
+    }
+
+    (words, quote)
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn function_split_words() {
+        assert_eq!(split_words(""), Ok(Vec::new()));
+        assert_eq!(split_words("  \t "), Ok(Vec::new()));
+        assert_eq!(
+            split_words("  set  --name=x\tfile.txt "),
+            Ok(vec![
+                "set".to_string(),
+                "--name=x".to_string(),
+                "file.txt".to_string()
+            ])
+        );
+        assert_eq!(
+            split_words(r#"a'b c'"d e" '' "x\"\\\$\`\n" 'a\b' \'"#),
+            Ok(vec![
+                "ab cd e".to_string(),
+                "".to_string(),
+                "x\"\\$`\\n".to_string(),
+                "a\\b".to_string(),
+                "'".to_string()
+            ])
+        );
+        assert_eq!(
+            split_words("é 'ü ö'"),
+            Ok(vec!["é".to_string(), "ü ö".to_string()])
+        );
+    }
+
+    #[test]
+    fn function_split_words_errors() {
+        assert_eq!(
+            split_words("set 'a"),
+            Err("unterminated single quote".to_string())
+        );
+        assert_eq!(
+            split_words("set \"a\\\""),
+            Err("unterminated double quote".to_string())
+        );
+        assert_eq!(
+            split_words("set a\\"),
+            Err("unterminated escape".to_string())
+        );
+    }
+
+    #[test]
+    fn function_split_word_spans() {
+        assert_eq!(
+            split_word_spans(" get 'a b'c"),
+            Ok(vec![
+                Word {
+                    text: "get".to_string(),
+                    start: 1,
+                    end: 4
+                },
+                Word {
+                    text: "a bc".to_string(),
+                    start: 5,
+                    end: 11
+                }
+            ])
+        );
+        assert_eq!(
+            split_words_partial("get \"a b"),
+            vec![
+                Word {
+                    text: "get".to_string(),
+                    start: 0,
+                    end: 3
+                },
+                Word {
+                    text: "a b".to_string(),
+                    start: 4,
+                    end: 8
+                }
+            ]
+        );
+    }
+}
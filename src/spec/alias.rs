@@ -0,0 +1,405 @@
+// Copyright 2018 Christopher Simpkins
+// Licensed under the MIT license
+
+//! Git-style command alias support
+
+use std::collections::HashMap;
+
+use errors::{ErrorKind, ParseError};
+use parsers::{self, Span, TokenKind};
+use spec::{Config, ConfigValue, Spec};
+use Command;
+
+// The expanded arguments and the index range of the expansion arguments
+type Expansion = (Vec<String>, Option<(usize, usize)>);
+
+/// A table of command aliases that are expanded in the first positional argument (e.g., the `co` subcommand in `tool co main` with the alias `co = checkout --quiet`)
+///
+/// # Remarks
+/// An alias expansion is a list of arguments that are split with shell quoting rules (see `parsers::split_words`).  The first argument of an expansion is expanded again when it is an alias, unless it is the alias that is expanded (e.g., `log = log --oneline`).  An alias that is expanded more than once in an expansion is an alias loop error.  An expansion with an unterminated quote is an invalid alias error when the alias is expanded.
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::spec::{AliasTable, OptionSpec, Spec};
+///
+/// let spec = Spec::new("tool").option(OptionSpec::flag("--quiet"));
+/// let aliases = AliasTable::new()
+///     .alias("co", "checkout --quiet")
+///     .alias("com", "co main");
+/// let argv: Vec<String> = vec!["tool", "com"].into_iter().map(String::from).collect();
+///
+/// let c = aliases.parse(&spec, &argv).unwrap();
+///
+/// assert_eq!(c.argv, vec!["tool", "checkout", "--quiet", "main"]);
+/// assert_eq!(c.original_argv, Some(argv));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AliasTable {
+    /// The alias expansions mapped as key=alias:value=expansion arguments
+    pub aliases: HashMap<String, Vec<String>>,
+    /// The failure messages of the alias expansions that cannot be split into arguments mapped as key=alias:value=message
+    pub invalid: HashMap<String, String>,
+}
+
+impl AliasTable {
+    /// Instantiates and returns a new, empty `AliasTable` struct
+    pub fn new() -> Self {
+        AliasTable::default()
+    }
+
+    /// Returns `Result<AliasTable, String>` with the aliases that are defined in the `section` of the configuration file `config` (e.g., `co = "checkout --quiet"` in an `[alias]` section), or a failure message
+    ///
+    /// # Remarks
+    /// An expansion is a string or an array of strings.
+    pub fn from_config(config: &Config, section: &str) -> Result<AliasTable, String> {
+        let prefix = format!("{}.", section);
+        let mut table = AliasTable::new();
+        for (key, value) in &config.entries {
+            let name = match key.strip_prefix(&prefix) {
+                Some(x) => x,
+                None => continue,
+            };
+            let expansion: Vec<String> = match value {
+                ConfigValue::String(x) => {
+                    table.insert(name, x);
+                    continue;
+                }
+                ConfigValue::Array(items) => items.iter().map(|x| x.to_string()).collect(),
+                _ => return Err(format!("the expansion of alias '{}' is not a string", name)),
+            };
+            table.aliases.insert(name.to_string(), expansion);
+        }
+        Ok(table)
+    }
+
+    /// Returns the `AliasTable` with the alias `name` that expands to the arguments in `expansion`, which are split with shell quoting rules
+    pub fn alias(mut self, name: &str, expansion: &str) -> Self {
+        self.insert(name, expansion);
+        self
+    }
+
+    // Inserts the alias `name` with the arguments of `expansion`, or the failure message when the
+    // expansion cannot be split into arguments
+    fn insert(&mut self, name: &str, expansion: &str) {
+        match parsers::split_words(expansion) {
+            Ok(words) => {
+                self.invalid.remove(name);
+                self.aliases.insert(name.to_string(), words);
+            }
+            Err(message) => {
+                self.aliases.remove(name);
+                self.invalid.insert(name.to_string(), message);
+            }
+        }
+    }
+
+    /// Returns `Option<&[String]>` with the expansion arguments of the alias `name`.
+    /// Returns `None` if the alias is not defined.
+    pub fn get(&self, name: &str) -> Option<&[String]> {
+        self.aliases.get(name).map(|x| &x[..])
+    }
+
+    /// Returns `Result<Vec<String>, ParseError>` with the command line arguments in `argv` with the first positional argument alias expanded.  The arguments are returned unchanged when the first positional argument is not an alias.
+    ///
+    /// Returns an `ErrorKind::InvalidAlias` error for an alias loop, an empty expansion, or an expansion with an unterminated quote.
+    pub fn expand(&self, spec: &Spec, argv: &[String]) -> Result<Vec<String>, ParseError> {
+        self.expand_at(spec, argv).map(|x| x.0)
+    }
+
+    /// Returns `Result<Command, ParseError>` with a `Command` that is parsed from the command line arguments in `argv` after the alias expansion
+    ///
+    /// # Remarks
+    /// The command line arguments before the alias expansion are defined in `Command.original_argv` when an alias is expanded.  The location of a `ParseError` is in `argv`.  An error in the expansion arguments is located at the alias.
+    pub fn parse(&self, spec: &Spec, argv: &[String]) -> Result<Command, ParseError> {
+        let (expanded, range) = self.expand_at(spec, argv)?;
+        let (index, end) = match range {
+            Some(x) => x,
+            None => return spec.parse(argv),
+        };
+        let mut command = spec.parse(&expanded).map_err(|e| {
+            let span = e.span.map(|span| {
+                if span.index < index {
+                    span
+                } else if span.index < end {
+                    Span {
+                        index,
+                        start: 0,
+                        end: argv[index].len(),
+                    }
+                } else {
+                    Span {
+                        index: span.index + index + 1 - end,
+                        ..span
+                    }
+                }
+            });
+            ParseError::new(e.kind, span)
+        })?;
+        command.original_argv = Some(argv.to_vec());
+
+        Ok(command)
+    }
+
+    // Returns the expanded arguments and the index range of the expansion arguments, or `None` if
+    // an alias is not expanded
+    fn expand_at(&self, spec: &Spec, argv: &[String]) -> Result<Expansion, ParseError> {
+        let tokens = spec.tokenize(argv);
+        let index = tokens
+            .iter()
+            .take_while(|x| x.kind != TokenKind::Terminator)
+            .find(|x| x.kind == TokenKind::Positional || x.kind == TokenKind::TrailingArg)
+            .map(|x| x.index);
+        let index = match index {
+            Some(x)
+                if self.aliases.contains_key(&argv[x]) || self.invalid.contains_key(&argv[x]) =>
+            {
+                x
+            }
+            _ => return Ok((argv.to_vec(), None)),
+        };
+
+        let span = Span {
+            index,
+            start: 0,
+            end: argv[index].len(),
+        };
+        let mut chain: Vec<String> = Vec::new();
+        let mut words: Vec<String> = vec![argv[index].clone()];
+        loop {
+            let name = words[0].clone();
+            let error = |message: String| {
+                ParseError::new(ErrorKind::InvalidAlias(name.clone(), message), Some(span))
+            };
+            if let Some(message) = self.invalid.get(&name) {
+                return Err(error(message.clone()));
+            }
+            let expansion = match self.get(&name) {
+                Some(x) => x,
+                None => break,
+            };
+            words.remove(0);
+            if chain.contains(&name) {
+                chain.push(name.clone());
+                return Err(error(format!("alias loop {}", chain.join(" -> "))));
+            }
+            if expansion.is_empty() {
+                return Err(error(String::from("the expansion is empty")));
+            }
+            chain.push(name.clone());
+            words.splice(0..0, expansion.iter().cloned());
+            // an alias that begins with its own name is expanded to the command that it shadows
+            if expansion[0] == name {
+                break;
+            }
+        }
+
+        let end = index + words.len();
+        let mut expanded = argv[..index].to_vec();
+        expanded.extend(words);
+        expanded.extend_from_slice(&argv[index + 1..]);
+        Ok((expanded, Some((index, end))))
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argv;
+    use parsers::ParseOrder;
+    use spec::OptionSpec;
+
+    fn test_spec() -> Spec {
+        Spec::new("tool")
+            .option(OptionSpec::flag("--quiet").short('q'))
+            .option(OptionSpec::value("-C"))
+    }
+
+    fn test_aliases() -> AliasTable {
+        AliasTable::new()
+            .alias("co", "checkout --quiet")
+            .alias("com", "co main")
+            .alias("loop1", "loop2 x")
+            .alias("loop2", "loop1")
+            .alias("empty", "")
+            .alias("bad", "checkout --bogus")
+            .alias("msg", "commit -m 'fix typo' --quiet")
+            .alias("log", "log --oneline")
+            .alias("lg", "log -q")
+            .alias("quote", "commit -m 'fix")
+    }
+
+    #[test]
+    fn alias_table_expand() {
+        let spec = test_spec();
+        let aliases = test_aliases();
+
+        assert_eq!(
+            aliases.expand(&spec, &argv(&["tool", "-C", "co", "com", "-q"])),
+            Ok(argv(&[
+                "tool", "-C", "co", "checkout", "--quiet", "main", "-q"
+            ]))
+        );
+        assert_eq!(
+            aliases.expand(&spec, &argv(&["tool", "-q", "--", "co"])),
+            Ok(argv(&["tool", "-q", "--", "co"]))
+        );
+        assert_eq!(
+            aliases.expand(&spec, &argv(&["tool", "status", "co"])),
+            Ok(argv(&["tool", "status", "co"]))
+        );
+        assert_eq!(
+            aliases.expand(
+                &spec.clone().order(ParseOrder::RequireOrder),
+                &argv(&["tool", "co", "file"])
+            ),
+            Ok(argv(&["tool", "checkout", "--quiet", "file"]))
+        );
+        assert_eq!(
+            aliases.expand(&spec, &argv(&["tool", "msg"])),
+            Ok(argv(&["tool", "commit", "-m", "fix typo", "--quiet"]))
+        );
+        assert_eq!(
+            aliases.expand(&spec, &argv(&["tool", "log", "-q"])),
+            Ok(argv(&["tool", "log", "--oneline", "-q"]))
+        );
+        assert_eq!(
+            aliases.expand(&spec, &argv(&["tool", "lg"])),
+            Ok(argv(&["tool", "log", "--oneline", "-q"]))
+        );
+    }
+
+    #[test]
+    fn alias_table_errors() {
+        let spec = test_spec();
+        let aliases = test_aliases();
+        let span = Some(Span {
+            index: 2,
+            start: 0,
+            end: 5,
+        });
+
+        assert_eq!(
+            aliases.expand(&spec, &argv(&["tool", "-q", "loop1"])),
+            Err(ParseError::new(
+                ErrorKind::InvalidAlias(
+                    "loop1".to_string(),
+                    "alias loop loop1 -> loop2 -> loop1".to_string()
+                ),
+                span
+            ))
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                aliases
+                    .expand(&spec, &argv(&["tool", "-q", "empty"]))
+                    .unwrap_err()
+            ),
+            "invalid alias 'empty': the expansion is empty"
+        );
+        assert_eq!(
+            aliases.expand(&spec, &argv(&["tool", "-q", "quote"])),
+            Err(ParseError::new(
+                ErrorKind::InvalidAlias(
+                    "quote".to_string(),
+                    "unterminated single quote".to_string()
+                ),
+                span
+            ))
+        );
+        assert_eq!(aliases.get("quote"), None);
+        assert_eq!(
+            aliases.expand(&spec, &argv(&["tool", "status"])),
+            Ok(argv(&["tool", "status"]))
+        );
+    }
+
+    #[test]
+    fn alias_table_parse() {
+        let spec = test_spec();
+        let aliases = test_aliases();
+        let c1 = aliases.parse(&spec, &argv(&["tool", "com", "-q"])).unwrap();
+        let c2 = aliases.parse(&spec, &argv(&["tool", "status"])).unwrap();
+        let e1 = aliases.parse(&spec, &argv(&["tool", "bad", "-q"]));
+        let e2 = aliases.parse(&spec, &argv(&["tool", "co", "-q", "--bogus"]));
+
+        assert_eq!(
+            c1.argv,
+            argv(&["tool", "checkout", "--quiet", "main", "-q"])
+        );
+        assert_eq!(c1.original_argv, Some(argv(&["tool", "com", "-q"])));
+        assert_eq!(c1.get_original_argv(), &argv(&["tool", "com", "-q"])[..]);
+        assert_eq!(c2.original_argv, None);
+        assert_eq!(c2.get_original_argv(), &argv(&["tool", "status"])[..]);
+        assert_eq!(
+            e1.unwrap_err(),
+            ParseError::new(
+                ErrorKind::InvalidOption("--bogus".to_string()),
+                Some(Span {
+                    index: 1,
+                    start: 0,
+                    end: 3
+                })
+            )
+        );
+        assert_eq!(
+            e2.unwrap_err(),
+            ParseError::new(
+                ErrorKind::InvalidOption("--bogus".to_string()),
+                Some(Span {
+                    index: 3,
+                    start: 0,
+                    end: 7
+                })
+            )
+        );
+    }
+
+    #[test]
+    fn alias_table_parse_sensitive_options() {
+        let spec = test_spec().option(OptionSpec::value("--password").sensitive());
+        let c = test_aliases()
+            .parse(
+                &spec,
+                &argv(&["tool", "--password", "x", "com", "--password=y"]),
+            )
+            .unwrap();
+
+        assert_eq!(
+            c.redacted().original_argv,
+            Some(argv(&[
+                "tool",
+                "--password",
+                "****",
+                "com",
+                "--password=****"
+            ]))
+        );
+        assert_eq!(
+            c.to_string(),
+            "Command: 'tool --password **** checkout --quiet main --password=****'"
+        );
+    }
+
+    #[test]
+    fn alias_table_from_config() {
+        let config = Config::parse_toml(
+            "[alias]\nco = \"checkout --quiet\"\nst = [\"status\", \"-s\"]\nci = \"commit -m 'wip\"\n[other]\nx = \"y\"",
+        )
+        .unwrap();
+        let aliases = AliasTable::from_config(&config, "alias").unwrap();
+
+        assert_eq!(aliases.aliases.len(), 2);
+        assert_eq!(
+            aliases.invalid.get("ci"),
+            Some(&"unterminated single quote".to_string())
+        );
+        assert_eq!(aliases.get("co"), Some(&argv(&["checkout", "--quiet"])[..]));
+        assert_eq!(aliases.get("st"), Some(&argv(&["status", "-s"])[..]));
+        assert!(
+            AliasTable::from_config(&Config::parse_toml("alias.x = 1").unwrap(), "alias").is_err()
+        );
+    }
+}
@@ -0,0 +1,423 @@
+// Copyright 2018 Christopher Simpkins
+// Licensed under the MIT license
+
+//! Command line argument Vector construction support
+
+use std::process;
+
+use errors::{ErrorKind, ParseError};
+use spec::Spec;
+
+/// A builder for the command line arguments of an executable that is defined with a `Spec`
+///
+/// # Remarks
+/// Options are formatted with the long option syntax (e.g., `--name=value`) by default, and with the short option syntax (e.g., `-n value`) when `ArgvBuilder::prefer_short` is used or the option does not define a long option.  Consecutive short switch options are formatted as a multi-option short syntax option (e.g., `-abc`).  A double hyphen `--` idiom is inserted before the first positional argument that begins with a hyphen.
+///
+/// The arguments are validated against the `Spec` before they are returned.  The option values that are read from files (e.g., `--token-file=path` or `@path`) are not read and not validated.
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::spec::{ArgvBuilder, OptionSpec, Spec};
+///
+/// let spec = Spec::new("tool")
+///     .option(OptionSpec::flag("--verbose").short('v'))
+///     .option(OptionSpec::value("--output").short('o'));
+///
+/// let argv = ArgvBuilder::new(&spec)
+///     .flag("--verbose")
+///     .value("--output", "out.txt")
+///     .positional("-input-")
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(argv, vec!["tool", "--verbose", "--output=out.txt", "--", "-input-"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ArgvBuilder<'a> {
+    spec: &'a Spec,
+    executable: String,
+    options: Vec<(String, Option<String>)>,
+    positionals: Vec<String>,
+    prefer_short: bool,
+}
+
+impl<'a> ArgvBuilder<'a> {
+    /// Instantiates and returns a new `ArgvBuilder` struct for the executable defined in `spec`
+    pub fn new(spec: &'a Spec) -> Self {
+        ArgvBuilder {
+            spec,
+            executable: spec.name.clone(),
+            options: Vec::new(),
+            positionals: Vec::new(),
+            prefer_short: false,
+        }
+    }
+
+    /// Returns the `ArgvBuilder` with the executable path `executable` in place of the `Spec` executable name
+    pub fn executable(mut self, executable: &str) -> Self {
+        self.executable = executable.to_string();
+        self
+    }
+
+    /// Returns the `ArgvBuilder` with the switch option `switch` (e.g., `-v` or `--verbose`)
+    pub fn flag(mut self, switch: &str) -> Self {
+        self.options.push((switch.to_string(), None));
+        self
+    }
+
+    /// Returns the `ArgvBuilder` with the option `switch` (e.g., `-o` or `--output`) defined as `value`
+    pub fn value(mut self, switch: &str, value: &str) -> Self {
+        self.options
+            .push((switch.to_string(), Some(value.to_string())));
+        self
+    }
+
+    /// Returns the `ArgvBuilder` with the option `switch` defined as the option value list `items` that is joined with the option value delimiter
+    ///
+    /// # Remarks
+    /// Delimiter and escape characters in the items are escaped when the option has an escape character.  The option is not included in the arguments when it is not defined in the `Spec`.  This is reported as an error by `ArgvBuilder::build`.
+    pub fn values(self, switch: &str, items: &[&str]) -> Self {
+        let value = match self.spec.find(switch) {
+            Some(option) => option.join_list(items),
+            None => items.concat(),
+        };
+        self.value(switch, &value)
+    }
+
+    /// Returns the `ArgvBuilder` with the positional argument `arg`
+    pub fn positional(mut self, arg: &str) -> Self {
+        self.positionals.push(arg.to_string());
+        self
+    }
+
+    /// Returns the `ArgvBuilder` defined to format options with the short option syntax when a short option is defined
+    pub fn prefer_short(mut self) -> Self {
+        self.prefer_short = true;
+        self
+    }
+
+    /// Returns `Result<Vec<String>, ParseError>` with the formatted command line arguments.
+    ///
+    /// Returns a `ParseError` if the arguments are not valid in the `Spec`.
+    pub fn build(&self) -> Result<Vec<String>, ParseError> {
+        let mut argv: Vec<String> = vec![self.executable.clone()];
+        let mut cluster = String::new();
+        for (switch, value) in &self.options {
+            let option = self.spec.find(switch).ok_or_else(|| {
+                ParseError::new(ErrorKind::InvalidOption(switch.to_string()), None)
+            })?;
+            if option.negates(switch) || option.decrements(switch) || option.reads_file(switch) {
+                push_cluster(&mut cluster, &mut argv);
+                argv.push(match value {
+                    Some(v) => format!("{}={}", switch, v),
+                    None => switch.clone(),
+                });
+                continue;
+            }
+            // a value of a short switch option would be formatted as a positional argument
+            if value.is_some() && !(option.takes_value() || option.takes_optional_value()) {
+                return Err(ParseError::new(
+                    ErrorKind::UnexpectedValue(option.key()),
+                    None,
+                ));
+            }
+            let short = if self.prefer_short || option.long.is_none() {
+                option.short
+            } else {
+                None
+            };
+            match (short, value) {
+                (Some(c), None) if option.takes_optional_value() => {
+                    // a following switch in a multi-option short syntax option would be its value
+                    push_cluster(&mut cluster, &mut argv);
+                    argv.push(format!("-{}", c));
+                }
+                (Some(c), Some(v)) if option.takes_optional_value() => {
+                    push_cluster(&mut cluster, &mut argv);
+                    argv.push(format!("-{}{}", c, v));
+                }
+                (Some(c), None) => {
+                    if cluster.is_empty() {
+                        cluster.push('-');
+                    }
+                    cluster.push(c);
+                }
+                (Some(c), Some(v)) => {
+                    push_cluster(&mut cluster, &mut argv);
+                    argv.push(format!("-{}", c));
+                    argv.push(v.clone());
+                }
+                (None, None) => {
+                    push_cluster(&mut cluster, &mut argv);
+                    argv.push(option.key());
+                }
+                (None, Some(v)) => {
+                    push_cluster(&mut cluster, &mut argv);
+                    argv.push(format!("{}={}", option.key(), v));
+                }
+            }
+        }
+        push_cluster(&mut cluster, &mut argv);
+
+        let mut terminated = false;
+        for arg in &self.positionals {
+            // Per POSIX guidelines, the double hyphen indicates that the
+            // arguments that follow it are not options
+            if !terminated && arg.starts_with('-') && arg != "-" {
+                argv.push(String::from("--"));
+                terminated = true;
+            }
+            argv.push(arg.clone());
+        }
+
+        self.spec.check_argv(&argv)?;
+        Ok(argv)
+    }
+
+    /// Returns `Result<std::process::Command, ParseError>` that is ready to execute the executable with the formatted command line arguments.
+    ///
+    /// Returns a `ParseError` if the arguments are not valid in the `Spec`.
+    pub fn command(&self) -> Result<process::Command, ParseError> {
+        let argv = self.build()?;
+        let mut command = process::Command::new(&argv[0]);
+        command.args(&argv[1..]);
+        Ok(command)
+    }
+}
+
+// Pushes a pending multi-option short syntax option to `argv`
+fn push_cluster(cluster: &mut String, argv: &mut Vec<String>) {
+    if !cluster.is_empty() {
+        argv.push(cluster.clone());
+        cluster.clear();
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spec::OptionSpec;
+
+    fn test_spec() -> Spec {
+        Spec::new("tool")
+            .option(OptionSpec::flag("--verbose").short('v'))
+            .option(OptionSpec::flag("-x"))
+            .option(OptionSpec::flag("--quiet"))
+            .option(OptionSpec::value("--name").short('n'))
+    }
+
+    #[test]
+    fn argv_builder_long_options() {
+        let spec = test_spec();
+        let argv = ArgvBuilder::new(&spec)
+            .flag("-v")
+            .value("-n", "value")
+            .flag("-x")
+            .positional("pos")
+            .build()
+            .unwrap();
+        assert_eq!(argv, vec!["tool", "--verbose", "--name=value", "-x", "pos"]);
+    }
+
+    #[test]
+    fn argv_builder_short_options() {
+        let spec = test_spec();
+        let argv = ArgvBuilder::new(&spec)
+            .prefer_short()
+            .flag("--verbose")
+            .flag("-x")
+            .value("--name", "-value")
+            .flag("--quiet") // no short option defined
+            .build()
+            .unwrap();
+        assert_eq!(argv, vec!["tool", "-vx", "-n", "-value", "--quiet"]);
+    }
+
+    #[test]
+    fn argv_builder_negatable_options() {
+        let spec = test_spec().option(OptionSpec::flag("--color").short('c').negatable());
+        let argv = ArgvBuilder::new(&spec)
+            .prefer_short()
+            .flag("--color")
+            .flag("--no-color")
+            .build()
+            .unwrap();
+        assert_eq!(argv, vec!["tool", "-c", "--no-color"]);
+
+        let e = ArgvBuilder::new(&spec).value("--no-color", "x").build();
+        assert_eq!(
+            e.unwrap_err().kind,
+            ErrorKind::UnexpectedValue("--no-color".to_string())
+        );
+    }
+
+    #[test]
+    fn argv_builder_counted_options() {
+        let spec = test_spec().option(OptionSpec::count("-d").decrement("--quiet-debug"));
+        let argv = ArgvBuilder::new(&spec)
+            .flag("-d")
+            .flag("-d")
+            .flag("--quiet-debug")
+            .build()
+            .unwrap();
+        assert_eq!(argv, vec!["tool", "-dd", "--quiet-debug"]);
+    }
+
+    #[test]
+    fn argv_builder_optional_value_options() {
+        let spec = test_spec().option(OptionSpec::optional_value("--color", "auto").short('c'));
+        let argv1 = ArgvBuilder::new(&spec)
+            .flag("--color")
+            .value("--color", "never")
+            .build()
+            .unwrap();
+        let argv2 = ArgvBuilder::new(&spec)
+            .prefer_short()
+            .flag("-c")
+            .flag("-v")
+            .value("-c", "never")
+            .build()
+            .unwrap();
+        assert_eq!(argv1, vec!["tool", "--color", "--color=never"]);
+        assert_eq!(argv2, vec!["tool", "-c", "-v", "-cnever"]);
+    }
+
+    #[test]
+    fn argv_builder_file_options() {
+        let spec = test_spec().option(OptionSpec::value("--token").file_option("--token-file"));
+        let argv = ArgvBuilder::new(&spec)
+            .prefer_short()
+            .value("--token-file", "/dev/null")
+            .build()
+            .unwrap();
+        assert_eq!(argv, vec!["tool", "--token-file=/dev/null"]);
+
+        // the files are read by the executable, not when the arguments are built
+        let spec = test_spec()
+            .option(
+                OptionSpec::value("--token")
+                    .file_option("--token-file")
+                    .required(),
+            )
+            .option(OptionSpec::value("--body").choices(&["a", "b"]).at_file())
+            .option(OptionSpec::map("--header").at_file());
+        let argv = ArgvBuilder::new(&spec)
+            .value("--token-file", "/missing/remote-token")
+            .value("--body", "@/missing/remote.json")
+            .value("--header", "@/missing/headers.txt")
+            .build()
+            .unwrap();
+        assert_eq!(
+            argv,
+            vec![
+                "tool",
+                "--token-file=/missing/remote-token",
+                "--body=@/missing/remote.json",
+                "--header=@/missing/headers.txt"
+            ]
+        );
+        let e = ArgvBuilder::new(&spec)
+            .value("--token-file", "/missing/remote-token")
+            .value("--body", "c")
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            e.kind,
+            ErrorKind::InvalidChoice(
+                "--body".to_string(),
+                "c".to_string(),
+                vec!["a".to_string(), "b".to_string()]
+            )
+        );
+        assert!(spec
+            .parse(&argv)
+            .is_err_and(|e| matches!(e.kind, ErrorKind::InvalidValue(..))));
+    }
+
+    #[test]
+    fn argv_builder_list_values() {
+        let spec = test_spec().option(OptionSpec::value("--tags").delimiter(',').escape('\\'));
+        let argv = ArgvBuilder::new(&spec)
+            .values("--tags", &["a", "b,c"])
+            .build()
+            .unwrap();
+        assert_eq!(argv, vec!["tool", "--tags=a,b\\,c"]);
+        assert!(ArgvBuilder::new(&spec)
+            .values("--bogus", &["a"])
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn argv_builder_double_hyphen_insertion() {
+        let spec = test_spec();
+        let argv = ArgvBuilder::new(&spec)
+            .executable("/usr/bin/tool")
+            .positional("-")
+            .positional("first")
+            .positional("--second")
+            .positional("-third")
+            .build()
+            .unwrap();
+        assert_eq!(
+            argv,
+            vec!["/usr/bin/tool", "-", "first", "--", "--second", "-third"]
+        );
+    }
+
+    #[test]
+    fn argv_builder_validation() {
+        let spec = test_spec().option(OptionSpec::value("--required").required());
+        let e1 = ArgvBuilder::new(&spec).flag("--bogus").build();
+        let e2 = ArgvBuilder::new(&spec).flag("--name").build();
+        let e3 = ArgvBuilder::new(&spec).value("-v", "yes").build();
+        let e4 = ArgvBuilder::new(&spec).flag("-v").build();
+        let e5 = ArgvBuilder::new(&spec)
+            .prefer_short()
+            .value("-v", "yes")
+            .build();
+        let e6 = ArgvBuilder::new(&spec).value("-x", "yes").build();
+
+        assert_eq!(
+            e1.unwrap_err().kind,
+            ErrorKind::InvalidOption("--bogus".to_string())
+        );
+        assert_eq!(
+            e2.unwrap_err().kind,
+            ErrorKind::MissingValue("--name".to_string())
+        );
+        assert_eq!(
+            e3.unwrap_err().kind,
+            ErrorKind::UnexpectedValue("--verbose".to_string())
+        );
+        assert_eq!(
+            e4.unwrap_err().kind,
+            ErrorKind::MissingRequired("--required".to_string())
+        );
+        assert_eq!(
+            e5.unwrap_err().kind,
+            ErrorKind::UnexpectedValue("--verbose".to_string())
+        );
+        assert_eq!(
+            e6.unwrap_err().kind,
+            ErrorKind::UnexpectedValue("-x".to_string())
+        );
+    }
+
+    #[test]
+    fn argv_builder_command() {
+        let spec = test_spec();
+        let command = ArgvBuilder::new(&spec)
+            .flag("-v")
+            .positional("pos")
+            .command()
+            .unwrap();
+        assert_eq!(command.get_program(), "tool");
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            vec!["--verbose", "pos"]
+        );
+    }
+}
@@ -0,0 +1,1003 @@
+// Copyright 2018 Christopher Simpkins
+// Licensed under the MIT license
+
+//! Configuration file and environment variable option value layering support
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+
+use errors::{ErrorKind, ParseError};
+use spec::{map_value, option_value, resolve_value, OptionKind, OptionSpec, Spec, Value};
+use utilities::path::expand_tilde;
+use Command;
+
+/// The configuration layer that defined an option value
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ValueSource {
+    /// The configuration file at the path
+    Config(PathBuf),
+    /// The environment variable with the name
+    Env(String),
+    /// The command line arguments
+    Argv,
+    /// The interactive prompt of a `Prompter`
+    Prompt,
+}
+
+/// A configuration file value
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ConfigValue {
+    /// A string value
+    String(String),
+    /// An integer value
+    Integer(i64),
+    /// A floating point value
+    Float(f64),
+    /// A boolean value
+    Boolean(bool),
+    /// An array of values
+    Array(Vec<ConfigValue>),
+}
+
+// Display trait
+impl fmt::Display for ConfigValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigValue::String(x) => write!(f, "{}", x),
+            ConfigValue::Integer(x) => write!(f, "{}", x),
+            ConfigValue::Float(x) => write!(f, "{}", x),
+            ConfigValue::Boolean(x) => write!(f, "{}", x),
+            ConfigValue::Array(x) => {
+                let items: Vec<String> = x.iter().map(|item| item.to_string()).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+        }
+    }
+}
+
+/// A parsed TOML or INI configuration file
+///
+/// # Remarks
+/// Keys in a section are prefixed with the section name (e.g., `section.key`).  The TOML format is limited to single line `key = value` definitions with string, integer, float, boolean, and array values, and `[section]` tables.  Multi-line strings and arrays, inline tables, arrays of tables, and unicode escape sequences are reported as unsupported TOML syntax.  INI values are strings.
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::spec::{Config, ConfigValue};
+///
+/// let config = Config::parse_toml("verbose = true\n[define]\nNAME = \"value\"").unwrap();
+///
+/// assert_eq!(config.get("verbose"), Some(&ConfigValue::Boolean(true)));
+/// assert_eq!(config.get("define.NAME"), Some(&ConfigValue::String("value".to_string())));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Config {
+    /// The (key, value) entries in the order that they are defined
+    pub entries: Vec<(String, ConfigValue)>,
+}
+
+impl Config {
+    /// Returns `Result<Config, String>` with the configuration in the TOML formatted `text`, or a failure message with the line number
+    ///
+    /// Returns an `unsupported TOML syntax` failure message for TOML syntax that is not supported (e.g., `line 2: unsupported TOML syntax: inline tables`).
+    pub fn parse_toml(text: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+        let mut section = String::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = strip_toml_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: &str| format!("line {}: {}", n + 1, message);
+            if line.starts_with("[[") {
+                return Err(error(&unsupported("arrays of tables")));
+            }
+            if let Some(header) = line.strip_prefix('[') {
+                section = match header.strip_suffix(']') {
+                    Some(x) if !x.trim().is_empty() => unquote(x.trim()).to_string(),
+                    _ => return Err(error("invalid table header")),
+                };
+                continue;
+            }
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) if !key.trim().is_empty() => (unquote(key.trim()), value),
+                _ => return Err(error("expected a key = value definition")),
+            };
+            let mut chars = value.chars().peekable();
+            let value = parse_toml_value(&mut chars).map_err(|e| error(&e))?;
+            skip_whitespace(&mut chars);
+            if chars.peek().is_some() {
+                return Err(error("unexpected characters after the value"));
+            }
+            config.entries.push((section_key(&section, key), value));
+        }
+        Ok(config)
+    }
+
+    /// Returns `Result<Config, String>` with the configuration in the INI formatted `text`, or a failure message with the line number
+    ///
+    /// # Remarks
+    /// Definitions use the `key = value` or `key: value` syntax.  Lines that begin with `;` or `#` are comments.  Matching quotes that surround a value are removed.
+    pub fn parse_ini(text: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+        let mut section = String::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[') {
+                section = match header.strip_suffix(']') {
+                    Some(x) if !x.trim().is_empty() => x.trim().to_string(),
+                    _ => return Err(format!("line {}: invalid section header", n + 1)),
+                };
+                continue;
+            }
+            let (key, value) = match line.find(['=', ':']) {
+                Some(i) if !line[..i].trim().is_empty() => (line[..i].trim(), line[i + 1..].trim()),
+                _ => return Err(format!("line {}: expected a key = value definition", n + 1)),
+            };
+            config.entries.push((
+                section_key(&section, key),
+                ConfigValue::String(unquote(value).to_string()),
+            ));
+        }
+        Ok(config)
+    }
+
+    /// Returns `Result<Config, String>` with the configuration in the file at `path`, or a failure message
+    ///
+    /// # Remarks
+    /// Files with the `.ini`, `.cfg`, and `.conf` extensions are parsed as INI files and other files are parsed as TOML files.
+    pub fn load(path: &Path) -> Result<Config, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        match path.extension().and_then(|x| x.to_str()) {
+            Some("ini") | Some("cfg") | Some("conf") => Config::parse_ini(&text),
+            _ => Config::parse_toml(&text),
+        }
+    }
+
+    /// Returns `Option<&ConfigValue>` with the last value of the entry `key`.
+    /// Returns `None` if the key is not defined.
+    pub fn get(&self, key: &str) -> Option<&ConfigValue> {
+        self.entries.iter().rev().find(|x| x.0 == key).map(|x| &x.1)
+    }
+}
+
+/// A parser that resolves option values from a configuration file layer, an environment variable layer, and a command line argument layer, in increasing order of precedence
+///
+/// # Remarks
+/// The configuration file key of an option is the long option name, with underscores or hyphens (e.g., `max_size` or `max-size` for `--max-size`).  The entries of a map option are defined in a section with the long option name (e.g., `[define]`).  The environment variable of an option is defined with `OptionSpec::env` or with an `ConfigLayers::env_prefix` and the upper case long option name (e.g., `TOOL_MAX_SIZE`).
+///
+/// The highest precedence layer that defines an option replaces its value in lower layers, and is recorded in `Command.value_sources`.  Required options may be defined in any layer.
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::spec::{ConfigLayers, OptionSpec, Spec, ValueSource};
+/// use std::{env, fs, process};
+///
+/// let path = env::temp_dir().join(format!("commandlines-doc-{}.toml", process::id()));
+/// fs::write(&path, "output = \"file.txt\"\nverbose = false\n").unwrap();
+///
+/// let spec = Spec::new("tool")
+///     .option(OptionSpec::value("--config"))
+///     .option(OptionSpec::value("--output"))
+///     .option(OptionSpec::flag("--verbose"));
+///
+/// let argv: Vec<String> = vec!["tool", "--verbose"].into_iter().map(String::from).collect();
+/// let c = ConfigLayers::new(&spec)
+///     .config_file(path.to_str().unwrap())
+///     .config_option("--config")
+///     .env_prefix("TOOL")
+///     .env_vars(&[("TOOL_OUTPUT", "out.txt")])
+///     .parse(&argv)
+///     .unwrap();
+///
+/// assert_eq!(c.get_value("--output").and_then(|v| v.as_str()), Some("out.txt"));
+/// assert_eq!(c.get_value_source("--output"), Some(&ValueSource::Env("TOOL_OUTPUT".to_string())));
+/// assert_eq!(c.get_value_source("--verbose"), Some(&ValueSource::Argv));
+/// # fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConfigLayers<'a> {
+    spec: &'a Spec,
+    config_file: Option<PathBuf>,
+    config_option: Option<String>,
+    env_prefix: Option<String>,
+    env_vars: Option<HashMap<String, String>>,
+}
+
+impl<'a> ConfigLayers<'a> {
+    /// Instantiates and returns a new `ConfigLayers` struct for `spec`
+    pub fn new(spec: &'a Spec) -> Self {
+        ConfigLayers {
+            spec,
+            config_file: None,
+            config_option: None,
+            env_prefix: None,
+            env_vars: None,
+        }
+    }
+
+    /// Returns the `ConfigLayers` with the default configuration file path `path`.  A leading `~` is expanded to the home directory.
+    ///
+    /// # Remarks
+    /// A default configuration file that does not exist is not an error.
+    pub fn config_file(mut self, path: &str) -> Self {
+        self.config_file = Some(expand_tilde(path));
+        self
+    }
+
+    /// Returns the `ConfigLayers` with the option `switch` (e.g., `--config`) that defines the configuration file path in place of the default path
+    ///
+    /// # Remarks
+    /// The option value is read from the command line arguments or the environment variable of the option.  A configuration file that is defined with the option must exist.
+    pub fn config_option(mut self, switch: &str) -> Self {
+        self.config_option = Some(switch.to_string());
+        self
+    }
+
+    /// Returns the `ConfigLayers` with the environment variable name prefix `prefix` (e.g., `TOOL` for `TOOL_OUTPUT`)
+    pub fn env_prefix(mut self, prefix: &str) -> Self {
+        self.env_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Returns the `ConfigLayers` with the environment variables in `vars` in place of the process environment
+    pub fn env_vars(mut self, vars: &[(&str, &str)]) -> Self {
+        self.env_vars = Some(
+            vars.iter()
+                .map(|x| (x.0.to_string(), x.1.to_string()))
+                .collect(),
+        );
+        self
+    }
+
+    /// Returns `Option<String>` with the environment variable name for `option`.
+    /// Returns `None` if the option does not define an environment variable and there is no environment variable prefix.
+    pub fn env_name(&self, option: &OptionSpec) -> Option<String> {
+        match (&option.env, &self.env_prefix) {
+            (Some(x), _) => Some(x.clone()),
+            (None, Some(prefix)) => Some(format!(
+                "{}_{}",
+                prefix,
+                config_name(option).to_uppercase().replace('-', "_")
+            )),
+            (None, None) => None,
+        }
+    }
+
+    /// Returns `Result<Command, ParseError>` with a `Command` that is parsed from the command line arguments in `argv` with the option values of the configuration file and environment variable layers
+    pub fn parse(&self, argv: &[String]) -> Result<Command, ParseError> {
+        let (tokens, argv_values) = self.spec.parse_argv(argv)?;
+        let mut values: HashMap<String, Value> = HashMap::new();
+        let mut sources: HashMap<String, ValueSource> = HashMap::new();
+
+        if let Some(path) = self.config_path(&argv_values)? {
+            let config = Config::load(&path).map_err(|e| {
+                ParseError::new(
+                    ErrorKind::InvalidConfig(path.display().to_string(), e),
+                    None,
+                )
+            })?;
+            let source = format!("configuration file '{}'", path.display());
+            for option in &self.spec.options {
+                if let Some(value) = config_option_value(option, &config)
+                    .map_err(|e| with_source(self.spec.redact_error(e), &source))?
+                {
+                    values.insert(option.key(), value);
+                    sources.insert(option.key(), ValueSource::Config(path.clone()));
+                }
+            }
+        }
+
+        for option in &self.spec.options {
+            let name = match self.env_name(option) {
+                Some(x) => x,
+                None => continue,
+            };
+            if let Some(text) = self.get_env(&name) {
+                let source = format!("environment variable '{}'", name);
+                let value = env_option_value(option, text)
+                    .map_err(|e| with_source(self.spec.redact_error(e), &source))?;
+                values.insert(option.key(), value);
+                sources.insert(option.key(), ValueSource::Env(name));
+            }
+        }
+
+        for (key, value) in argv_values {
+            sources.insert(key.clone(), ValueSource::Argv);
+            values.insert(key, value);
+        }
+
+        self.spec.check_required(&values)?;
+
+        Ok(self.spec.command(argv, tokens, values, sources))
+    }
+
+    // Returns the value of the environment variable `name`
+    fn get_env(&self, name: &str) -> Option<String> {
+        match &self.env_vars {
+            Some(vars) => vars.get(name).cloned(),
+            None => env::var(name).ok(),
+        }
+    }
+
+    // Returns the configuration file path that is defined with the configuration file option, or
+    // the default configuration file path if it exists
+    fn config_path(
+        &self,
+        argv_values: &HashMap<String, Value>,
+    ) -> Result<Option<PathBuf>, ParseError> {
+        let option = self.config_option.as_ref().and_then(|x| self.spec.find(x));
+        if let Some(option) = option {
+            let defined = match argv_values.get(&option.key()).and_then(|x| x.as_str()) {
+                Some(x) => Some(x.to_string()),
+                None => self.env_name(option).and_then(|x| self.get_env(&x)),
+            };
+            if let Some(path) = defined {
+                let path = expand_tilde(path);
+                if !path.is_file() {
+                    return Err(ParseError::new(
+                        ErrorKind::InvalidConfig(
+                            path.display().to_string(),
+                            String::from("the file does not exist"),
+                        ),
+                        None,
+                    ));
+                }
+                return Ok(Some(path));
+            }
+        }
+        Ok(self.config_file.clone().filter(|x| x.is_file()))
+    }
+}
+
+// Returns the configuration file key of `option` (e.g., `max-size` for `--max-size`)
+fn config_name(option: &OptionSpec) -> String {
+    option.key().trim_start_matches('-').to_string()
+}
+
+// Returns the option value of `option` that is defined in `config`
+fn config_option_value(option: &OptionSpec, config: &Config) -> Result<Option<Value>, ParseError> {
+    let name = config_name(option);
+    let matches = |key: &str| key.replace('_', "-") == name;
+    if option.kind == OptionKind::Map {
+        let prefix = format!("{}.", name);
+        let mut values: HashMap<String, Value> = HashMap::new();
+        for (key, value) in &config.entries {
+            let mut payloads: Vec<String> = Vec::new();
+            if matches(key) {
+                match value {
+                    ConfigValue::Array(items) => {
+                        payloads.extend(items.iter().map(|x| x.to_string()))
+                    }
+                    _ => payloads.push(value.to_string()),
+                }
+            } else if key.len() > prefix.len()
+                && key.is_char_boundary(prefix.len())
+                && key[..prefix.len()].replace('_', "-") == prefix
+            {
+                payloads.push(format!("{}={}", &key[prefix.len()..], value));
+            }
+            for payload in payloads {
+                let parsed = map_value(option, payload, None, &values)?;
+                values.insert(option.key(), parsed);
+            }
+        }
+        return Ok(values.remove(&option.key()));
+    }
+
+    let value = match config.entries.iter().rev().find(|x| matches(&x.0)) {
+        Some(x) => &x.1,
+        None => return Ok(None),
+    };
+    let invalid = |message: &str| {
+        ParseError::new(
+            ErrorKind::InvalidValue(option.key(), value.to_string(), message.to_string()),
+            None,
+        )
+    };
+    let parsed = match (&option.kind, value) {
+        (OptionKind::Flag, ConfigValue::Boolean(x)) => Value::Flag(*x),
+        (OptionKind::Flag, ConfigValue::String(x)) => {
+            Value::Flag(parse_bool(x).ok_or_else(|| invalid("expected a boolean"))?)
+        }
+        (OptionKind::Flag, _) => return Err(invalid("expected a boolean")),
+        (OptionKind::Count, ConfigValue::Integer(x)) => Value::Count(*x),
+        (OptionKind::Count, ConfigValue::Boolean(x)) => Value::Count(*x as i64),
+        (OptionKind::Count, ConfigValue::String(x)) => {
+            Value::Count(x.parse().map_err(|_| invalid("expected an integer"))?)
+        }
+        (OptionKind::Count, _) => return Err(invalid("expected an integer")),
+        (_, ConfigValue::Array(items)) if option.delimiter.is_some() => {
+            let mut list = Vec::new();
+            for item in items {
+                list.push(resolve_value(option, item.to_string(), None)?);
+            }
+            Value::List(list)
+        }
+        (_, ConfigValue::Array(_)) => return Err(invalid("expected a single value")),
+        (_, _) => option_value(option, value.to_string(), None, &HashMap::new())?,
+    };
+    Ok(Some(parsed))
+}
+
+// Returns the option value of `option` that is defined in the environment variable value `text`
+fn env_option_value(option: &OptionSpec, text: String) -> Result<Value, ParseError> {
+    let invalid = |message: &str| {
+        ParseError::new(
+            ErrorKind::InvalidValue(option.key(), text.clone(), message.to_string()),
+            None,
+        )
+    };
+    match option.kind {
+        OptionKind::Flag => parse_bool(&text)
+            .map(Value::Flag)
+            .ok_or_else(|| invalid("expected a boolean")),
+        OptionKind::Count => text
+            .trim()
+            .parse()
+            .map(Value::Count)
+            .map_err(|_| invalid("expected an integer")),
+        OptionKind::Map => {
+            let mut values: HashMap<String, Value> = HashMap::new();
+            for payload in option.split_list(&text) {
+                let parsed = map_value(option, payload, None, &values)?;
+                values.insert(option.key(), parsed);
+            }
+            Ok(values
+                .remove(&option.key())
+                .unwrap_or(Value::Map(Vec::new())))
+        }
+        _ => option_value(option, text.clone(), None, &HashMap::new()),
+    }
+}
+
+// Returns the boolean for the boolean word `text` (e.g., `true`, `yes`, `on`, `1`)
+fn parse_bool(text: &str) -> Option<bool> {
+    match &text.trim().to_lowercase()[..] {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+// Returns `error` with the configuration layer `source` appended to an invalid value message
+fn with_source(error: ParseError, source: &str) -> ParseError {
+    match error.kind {
+        ErrorKind::InvalidValue(option, value, message) => ParseError::new(
+            ErrorKind::InvalidValue(option, value, format!("{} (from {})", message, source)),
+            error.span,
+        ),
+        _ => error,
+    }
+}
+
+// Returns `key` prefixed with the `section` name
+fn section_key(section: &str, key: &str) -> String {
+    if section.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", section, key)
+    }
+}
+
+// Returns `text` without matching surrounding quotes
+fn unquote(text: &str) -> &str {
+    for quote in &['"', '\''] {
+        if text.len() >= 2 && text.starts_with(*quote) && text.ends_with(*quote) {
+            return &text[1..text.len() - 1];
+        }
+    }
+    text
+}
+
+// Returns the TOML `line` without a comment that is not in a string
+fn strip_toml_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), _) if c == q && !escaped => quote = None,
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '#') => return &line[..i],
+            _ => {}
+        }
+        escaped = false;
+    }
+    line
+}
+
+// Returns the failure message for the TOML `syntax` that is not supported
+fn unsupported(syntax: &str) -> String {
+    format!("unsupported TOML syntax: {}", syntax)
+}
+
+// Skips whitespace characters in `chars`
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while let Some(c) = chars.peek() {
+        if !c.is_whitespace() {
+            break;
+        }
+        chars.next();
+    }
+}
+
+// Parses a TOML value from `chars`
+fn parse_toml_value(chars: &mut Peekable<Chars>) -> Result<ConfigValue, String> {
+    skip_whitespace(chars);
+    match chars.peek() {
+        Some('"') => {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') if text.is_empty() && chars.peek() == Some(&'"') => {
+                        return Err(unsupported("multi-line strings"))
+                    }
+                    Some('"') => return Ok(ConfigValue::String(text)),
+                    Some('\\') => match chars.next() {
+                        Some('"') => text.push('"'),
+                        Some('\\') => text.push('\\'),
+                        Some('n') => text.push('\n'),
+                        Some('t') => text.push('\t'),
+                        Some('r') => text.push('\r'),
+                        Some('u') | Some('U') => {
+                            return Err(unsupported("unicode escape sequences"))
+                        }
+                        Some(c) => return Err(format!("invalid escape sequence '\\{}'", c)),
+                        None => return Err(String::from("unterminated string")),
+                    },
+                    Some(c) => text.push(c),
+                    None => return Err(String::from("unterminated string")),
+                }
+            }
+        }
+        Some('\'') => {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('\'') => return Ok(ConfigValue::String(text)),
+                    Some(c) => text.push(c),
+                    None => return Err(String::from("unterminated string")),
+                }
+            }
+        }
+        Some('[') => {
+            chars.next();
+            let mut items = Vec::new();
+            loop {
+                skip_whitespace(chars);
+                match chars.peek() {
+                    Some(']') => {
+                        chars.next();
+                        return Ok(ConfigValue::Array(items));
+                    }
+                    // the array continues on the next line
+                    None => return Err(unsupported("multi-line arrays")),
+                    Some(_) => {}
+                }
+                items.push(parse_toml_value(chars)?);
+                skip_whitespace(chars);
+                match chars.next() {
+                    Some(',') => {}
+                    Some(']') => return Ok(ConfigValue::Array(items)),
+                    None => return Err(unsupported("multi-line arrays")),
+                    Some(_) => return Err(String::from("unterminated array")),
+                }
+            }
+        }
+        Some('{') => Err(unsupported("inline tables")),
+        Some(_) => {
+            let mut token = String::new();
+            while let Some(c) = chars.peek() {
+                if c.is_whitespace() || *c == ',' || *c == ']' {
+                    break;
+                }
+                token.push(*c);
+                chars.next();
+            }
+            let number = token.replace('_', "");
+            match &token[..] {
+                "true" => Ok(ConfigValue::Boolean(true)),
+                "false" => Ok(ConfigValue::Boolean(false)),
+                _ => match (number.parse::<i64>(), number.parse::<f64>()) {
+                    (Ok(x), _) => Ok(ConfigValue::Integer(x)),
+                    (_, Ok(x))
+                        if number
+                            .starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+') =>
+                    {
+                        Ok(ConfigValue::Float(x))
+                    }
+                    _ => Err(format!("invalid value '{}'", token)),
+                },
+            }
+        }
+        None => Err(String::from("missing value")),
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argv;
+    use spec::DuplicateKeys;
+
+    // Returns the path of a new configuration file with `text` in the temporary directory
+    fn config_file(name: &str, text: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!(
+            "commandlines-config-{}-{}",
+            std::process::id(),
+            name
+        ));
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    fn test_spec() -> Spec {
+        Spec::new("tool")
+            .option(OptionSpec::value("--config").short('c'))
+            .option(OptionSpec::value("--output").required())
+            .option(OptionSpec::flag("--color").negatable())
+            .option(OptionSpec::count("-v").long("verbose"))
+            .option(OptionSpec::value("--max-size"))
+            .option(OptionSpec::value("--tags").delimiter(','))
+            .option(
+                OptionSpec::map("-D")
+                    .long("define")
+                    .duplicate_keys(DuplicateKeys::Error),
+            )
+            .option(
+                OptionSpec::value("--level")
+                    .choices(&["low", "high"])
+                    .env("LEVEL"),
+            )
+            .option(
+                OptionSpec::value("--key")
+                    .choices(&["k1", "k2"])
+                    .sensitive(),
+            )
+    }
+
+    #[test]
+    fn config_parse_toml() {
+        let config = Config::parse_toml(
+            "# comment\n\
+             output = \"out # not a comment.txt\" # comment\n\
+             max_size = 1_024\n\
+             ratio = 0.5\n\
+             color = false\n\
+             tags = [\"a\", 'b\\\\c', 3, [true]]\n\
+             [define]\n\
+             \"NAME\" = \"x\\\"y\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.get("output"),
+            Some(&ConfigValue::String("out # not a comment.txt".to_string()))
+        );
+        assert_eq!(config.get("max_size"), Some(&ConfigValue::Integer(1_024)));
+        assert_eq!(config.get("ratio"), Some(&ConfigValue::Float(0.5)));
+        assert_eq!(config.get("color"), Some(&ConfigValue::Boolean(false)));
+        assert_eq!(
+            config.get("tags").unwrap().to_string(),
+            "[a, b\\\\c, 3, [true]]"
+        );
+        assert_eq!(
+            config.get("define.NAME"),
+            Some(&ConfigValue::String("x\"y".to_string()))
+        );
+        assert_eq!(config.get("NAME"), None);
+        assert_eq!(
+            Config::parse_toml("a = 1\nb = 'x"),
+            Err(String::from("line 2: unterminated string"))
+        );
+        assert_eq!(
+            Config::parse_toml("a = {b = 1}"),
+            Err(String::from(
+                "line 1: unsupported TOML syntax: inline tables"
+            ))
+        );
+        assert_eq!(
+            Config::parse_toml("a = 1\ntags = [\n  \"x\",\n]"),
+            Err(String::from(
+                "line 2: unsupported TOML syntax: multi-line arrays"
+            ))
+        );
+        assert_eq!(
+            Config::parse_toml("tags = [\"x\","),
+            Err(String::from(
+                "line 1: unsupported TOML syntax: multi-line arrays"
+            ))
+        );
+        assert_eq!(
+            Config::parse_toml("a = \"\\u00e9\""),
+            Err(String::from(
+                "line 1: unsupported TOML syntax: unicode escape sequences"
+            ))
+        );
+        assert_eq!(
+            Config::parse_toml("a = \"\"\"\ntext\n\"\"\""),
+            Err(String::from(
+                "line 1: unsupported TOML syntax: multi-line strings"
+            ))
+        );
+        assert_eq!(
+            Config::parse_toml("[[a]]"),
+            Err(String::from(
+                "line 1: unsupported TOML syntax: arrays of tables"
+            ))
+        );
+        assert_eq!(
+            Config::parse_toml("a = [1 2]"),
+            Err(String::from("line 1: unterminated array"))
+        );
+        assert_eq!(
+            Config::parse_toml("a = yes"),
+            Err(String::from("line 1: invalid value 'yes'"))
+        );
+        assert_eq!(
+            Config::parse_toml("a = 1 2"),
+            Err(String::from(
+                "line 1: unexpected characters after the value"
+            ))
+        );
+        assert!(Config::parse_toml("novalue").is_err());
+    }
+
+    #[test]
+    fn config_parse_ini() {
+        let config = Config::parse_ini(
+            "; comment\n\
+             output = out.txt\n\
+             color: \"no\"\n\
+             [define]\n\
+             NAME = a=b\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.entries,
+            vec![
+                (
+                    "output".to_string(),
+                    ConfigValue::String("out.txt".to_string())
+                ),
+                ("color".to_string(), ConfigValue::String("no".to_string())),
+                (
+                    "define.NAME".to_string(),
+                    ConfigValue::String("a=b".to_string())
+                ),
+            ]
+        );
+        assert_eq!(
+            Config::parse_ini("[]"),
+            Err(String::from("line 1: invalid section header"))
+        );
+        assert!(Config::parse_ini("novalue").is_err());
+    }
+
+    #[test]
+    fn config_layers_precedence_and_sources() {
+        let path = config_file(
+            "precedence.toml",
+            "output = \"config.txt\"\n\
+             color = true\n\
+             verbose = 2\n\
+             max_size = \"1MiB\"\n\
+             tags = [\"a,b\", \"c\"]\n\
+             [define]\n\
+             A = 1\n\
+             B = \"2\"\n",
+        );
+        let spec = test_spec();
+        let layers = ConfigLayers::new(&spec)
+            .config_file(path.to_str().unwrap())
+            .env_prefix("TOOL")
+            .env_vars(&[
+                ("TOOL_COLOR", "no"),
+                ("TOOL_MAX_SIZE", "2MiB"),
+                ("LEVEL", "high"),
+            ]);
+        let c = layers
+            .parse(&argv(&["tool", "--max-size=3MiB", "-v"]))
+            .unwrap();
+
+        assert_eq!(
+            c.get_value("--output"),
+            Some(&Value::Single("config.txt".to_string()))
+        );
+        assert_eq!(
+            c.get_value_source("--output"),
+            Some(&ValueSource::Config(path.clone()))
+        );
+        assert_eq!(c.get_value("--color"), Some(&Value::Flag(false)));
+        assert_eq!(
+            c.get_value_source("--color"),
+            Some(&ValueSource::Env("TOOL_COLOR".to_string()))
+        );
+        assert_eq!(
+            c.get_value("--max-size"),
+            Some(&Value::Single("3MiB".to_string()))
+        );
+        assert_eq!(c.get_value_source("--max-size"), Some(&ValueSource::Argv));
+        assert_eq!(c.get_value("--verbose"), Some(&Value::Count(1))); // argv replaces the config count
+        assert_eq!(
+            c.get_value("--tags"),
+            Some(&Value::List(vec!["a,b".to_string(), "c".to_string()]))
+        );
+        assert_eq!(
+            c.get_value("--define"),
+            Some(&Value::Map(vec![
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "2".to_string())
+            ]))
+        );
+        assert_eq!(
+            c.get_value_source("--level"),
+            Some(&ValueSource::Env("LEVEL".to_string()))
+        );
+        assert_eq!(c.get_value_source("--config"), None);
+        assert!(spec
+            .help()
+            .contains("[possible values: low, high] [env: LEVEL]\n"));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn config_layers_config_option() {
+        let default = config_file("default.toml", "output = \"default.txt\"\n");
+        let other = config_file("other.ini", "output = other.txt\n");
+        let spec = test_spec();
+        let layers = ConfigLayers::new(&spec)
+            .config_file(default.to_str().unwrap())
+            .config_option("--config")
+            .env_prefix("TOOL")
+            .env_vars(&[]);
+
+        let c1 = layers.parse(&argv(&["tool"])).unwrap();
+        let c2 = layers
+            .parse(&argv(&["tool", "-c", other.to_str().unwrap()]))
+            .unwrap();
+        let c3 = ConfigLayers::new(&spec)
+            .config_option("--config")
+            .env_prefix("TOOL")
+            .env_vars(&[("TOOL_CONFIG", other.to_str().unwrap())])
+            .parse(&argv(&["tool"]))
+            .unwrap();
+        let e1 = layers.parse(&argv(&["tool", "--config=/missing/config.toml"]));
+        let e2 = ConfigLayers::new(&spec)
+            .config_file("/missing/config.toml")
+            .parse(&argv(&["tool"]));
+
+        assert_eq!(
+            c1.get_value("--output"),
+            Some(&Value::Single("default.txt".to_string()))
+        );
+        assert_eq!(
+            c2.get_value("--output"),
+            Some(&Value::Single("other.txt".to_string()))
+        );
+        assert_eq!(
+            c2.get_value_source("--output"),
+            Some(&ValueSource::Config(other.clone()))
+        );
+        assert_eq!(
+            c3.get_value("--output"),
+            Some(&Value::Single("other.txt".to_string()))
+        );
+        assert_eq!(
+            e1.unwrap_err().kind,
+            ErrorKind::InvalidConfig(
+                "/missing/config.toml".to_string(),
+                "the file does not exist".to_string()
+            )
+        );
+        assert_eq!(
+            e2.unwrap_err().kind,
+            ErrorKind::MissingRequired("--output".to_string())
+        );
+
+        fs::remove_file(&default).unwrap();
+        fs::remove_file(&other).unwrap();
+    }
+
+    #[test]
+    fn config_layers_errors() {
+        let syntax = config_file("syntax.toml", "output = \n");
+        let valid = config_file("valid.toml", "output = \"x\"\n");
+        let types = config_file("types.toml", "output = \"x\"\ncolor = 3\n");
+        let duplicate = config_file(
+            "duplicate.toml",
+            "output = \"x\"\ndefine = [\"A=1\"]\n[define]\nA = 2\n",
+        );
+        let spec = test_spec();
+        let parse = |path: &PathBuf, vars: &[(&str, &str)]| {
+            ConfigLayers::new(&spec)
+                .config_file(path.to_str().unwrap())
+                .env_prefix("TOOL")
+                .env_vars(vars)
+                .parse(&argv(&["tool"]))
+        };
+
+        assert_eq!(
+            parse(&syntax, &[]).unwrap_err().kind,
+            ErrorKind::InvalidConfig(
+                syntax.display().to_string(),
+                "line 1: missing value".to_string()
+            )
+        );
+        assert_eq!(
+            format!("{}", parse(&types, &[]).unwrap_err()),
+            format!(
+                "invalid value '3' for option '--color': expected a boolean (from configuration file '{}')",
+                types.display()
+            )
+        );
+        assert_eq!(
+            parse(&duplicate, &[]).unwrap_err().kind,
+            ErrorKind::InvalidValue(
+                "--define".to_string(),
+                "A=2".to_string(),
+                format!(
+                    "duplicate key 'A' (from configuration file '{}')",
+                    duplicate.display()
+                )
+            )
+        );
+        assert_eq!(
+            format!("{}", parse(&valid, &[("TOOL_COLOR", "1"), ("TOOL_VERBOSE", "many")]).unwrap_err()),
+            "invalid value 'many' for option '--verbose': expected an integer (from environment variable 'TOOL_VERBOSE')"
+        );
+        assert_eq!(
+            parse(&valid, &[("TOOL_COLOR", "1"), ("LEVEL", "medium")])
+                .unwrap_err()
+                .kind,
+            ErrorKind::InvalidChoice(
+                "--level".to_string(),
+                "medium".to_string(),
+                vec!["low".to_string(), "high".to_string()]
+            )
+        );
+        assert_eq!(
+            parse(&valid, &[("TOOL_COLOR", "1"), ("TOOL_KEY", "secret")])
+                .unwrap_err()
+                .kind,
+            ErrorKind::InvalidChoice(
+                "--key".to_string(),
+                "****".to_string(),
+                vec!["k1".to_string(), "k2".to_string()]
+            )
+        );
+        assert_eq!(
+            parse(&valid, &[("TOOL_COLOR", "on"), ("TOOL_DEFINE", "A=1")])
+                .unwrap()
+                .get_value("--define")
+                .and_then(|x| x.get("A")),
+            Some("1")
+        );
+
+        fs::remove_file(&syntax).unwrap();
+        fs::remove_file(&valid).unwrap();
+        fs::remove_file(&types).unwrap();
+        fs::remove_file(&duplicate).unwrap();
+    }
+}
@@ -0,0 +1,2451 @@
+// Copyright 2018 Christopher Simpkins
+// Licensed under the MIT license
+
+//! Command line option specification support
+
+pub mod alias;
+pub mod argv;
+pub mod config;
+pub mod prompt;
+pub mod repl;
+
+pub use self::alias::AliasTable;
+pub use self::argv::ArgvBuilder;
+pub use self::config::{Config, ConfigLayers, ConfigValue, ValueSource};
+pub use self::prompt::Prompter;
+pub use self::repl::{Completion, LineParser};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use errors::{self, ErrorKind, ParseError};
+use parsers::{self, ParseOrder, Span, Token, TokenKind};
+use utilities::path::PathValue;
+use Command;
+
+/// The text that replaces the value of a sensitive option in redacted output
+pub const REDACTED: &str = "****";
+
+/// The default maximum size in bytes of a file that an option value is read from (1 MiB)
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// The kind of option argument that an option accepts
+#[derive(Clone, Debug, PartialEq)]
+pub enum OptionKind {
+    /// A switch option that does not take a value (e.g., `--verbose`)
+    Flag,
+    /// An option that requires a value (e.g., `--output=path`, `--output path`, `-o path`, `-opath`)
+    Value,
+    /// A switch option that is counted each time that it is used (e.g., `-vvv`)
+    Count,
+    /// An option with an optional value (e.g., `--color[=WHEN]`, `-c[WHEN]`) and the implicit value that is used when the option is used without a value
+    OptionalValue(String),
+    /// An option that requires a `KEY=VALUE` value that is collected in an ordered map (e.g., `-DNAME=VALUE`, `-D NAME=VALUE`, `--set a.b=c`)
+    Map,
+}
+
+/// The policy for a key that is defined more than once in the values of an `OptionKind::Map` option
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// A duplicate key is an error
+    Error,
+    /// The last value of a duplicate key replaces the previous value at the position of the first definition of the key
+    #[default]
+    LastWins,
+    /// All of the values of a duplicate key are collected in the order that they are defined
+    Collect,
+}
+
+/// A parsed option value in `Command.values`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Value {
+    /// The value of an `OptionKind::Flag` option
+    Flag(bool),
+    /// The value of an `OptionKind::Value` or `OptionKind::OptionalValue` option
+    Single(String),
+    /// The net occurrence count of an `OptionKind::Count` option
+    Count(i64),
+    /// The merged items of the delimited values of an option with a value delimiter
+    List(Vec<String>),
+    /// The ordered (key, value) entries of an `OptionKind::Map` option
+    Map(Vec<(String, String)>),
+}
+
+impl Value {
+    /// Returns `Option<bool>` with the value of a flag option.
+    /// Returns `None` if the value is not a flag value.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Flag(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    /// Returns `Option<i64>` with the net occurrence count of a counted option.
+    /// Returns `None` if the value is not a count value.
+    pub fn as_count(&self) -> Option<i64> {
+        match self {
+            Value::Count(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    /// Returns `Option<&str>` with the value of an option that takes a value.
+    /// Returns `None` if the value is not a single string value.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Single(x) => Some(&x[..]),
+            _ => None,
+        }
+    }
+
+    /// Returns `Option<&[String]>` with the items of the delimited values of an option with a value delimiter.
+    /// Returns `None` if the value is not a list value.
+    pub fn as_list(&self) -> Option<&[String]> {
+        match self {
+            Value::List(x) => Some(&x[..]),
+            _ => None,
+        }
+    }
+
+    /// Returns `Option<&[(String, String)]>` with the ordered (key, value) entries of a map option.
+    /// Returns `None` if the value is not a map value.
+    pub fn as_map(&self) -> Option<&[(String, String)]> {
+        match self {
+            Value::Map(x) => Some(&x[..]),
+            _ => None,
+        }
+    }
+
+    /// Returns `Option<&str>` with the last value of the map option entry `key`.
+    /// Returns `None` if the value is not a map value or the key is not defined.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.as_map()
+            .and_then(|x| x.iter().rev().find(|entry| entry.0 == key))
+            .map(|entry| &entry.1[..])
+    }
+
+    /// Returns `Option<T>` with the value of an option that takes a value parsed as the `FromStr` type `T`.
+    /// Returns `None` if the value is not a single string value or cannot be parsed as `T`.
+    pub fn parse<T: FromStr>(&self) -> Option<T> {
+        self.as_str().and_then(|x| x.parse().ok())
+    }
+
+    /// Returns `Option<T>` with the `ValueEnum` variant for the value of an option that takes a value.
+    /// Returns `None` if the value is not a single string value or is not a choice name of `T`.
+    pub fn to_enum<T: ValueEnum>(&self) -> Option<T> {
+        self.as_str().and_then(T::from_choice)
+    }
+
+    /// Returns `Value` with the string values replaced with `REDACTED`.  Flag values, count values, and map keys are not redacted.
+    pub fn redacted(&self) -> Value {
+        match self {
+            Value::Single(_) => Value::Single(REDACTED.to_string()),
+            Value::List(x) => Value::List(x.iter().map(|_| REDACTED.to_string()).collect()),
+            Value::Map(x) => Value::Map(
+                x.iter()
+                    .map(|entry| (entry.0.clone(), REDACTED.to_string()))
+                    .collect(),
+            ),
+            _ => self.clone(),
+        }
+    }
+}
+
+/// An option value validator or parser function that is defined with `OptionSpec::validator`, `OptionSpec::parser`, or `OptionSpec::value_type`
+///
+/// # Remarks
+/// The function returns the option value, or a failure message that is reported with `ErrorKind::InvalidValue`.
+#[derive(Clone)]
+pub struct Validator(Arc<ValidatorFn>);
+
+// The option value validator and parser function type
+type ValidatorFn = dyn Fn(&str) -> Result<String, String> + Send + Sync;
+
+impl Validator {
+    /// Returns `Result<String, String>` with the validated option value or the failure message for `value`
+    pub fn call(&self, value: &str) -> Result<String, String> {
+        (self.0)(value)
+    }
+}
+
+// Debug trait
+impl fmt::Debug for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Validator")
+    }
+}
+
+// PartialEq trait
+impl PartialEq for Validator {
+    fn eq(&self, other: &Validator) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// A user defined enum that is mapped from the choices of an option value
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::spec::{OptionSpec, Spec, ValueEnum};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Format {
+///     Json,
+///     Yaml,
+/// }
+///
+/// impl ValueEnum for Format {
+///     fn choices() -> Vec<&'static str> {
+///         vec!["json", "yaml"]
+///     }
+///
+///     fn from_choice(choice: &str) -> Option<Self> {
+///         match choice {
+///             "json" => Some(Format::Json),
+///             "yaml" => Some(Format::Yaml),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let spec = Spec::new("tool").option(
+///     OptionSpec::value("--format").value_enum::<Format>().choice_alias("yml", "yaml").ignore_case(),
+/// );
+/// let argv: Vec<String> = vec!["tool", "--format=YML"].into_iter().map(String::from).collect();
+/// let c = spec.parse(&argv).unwrap();
+///
+/// assert_eq!(c.get_value("--format").and_then(|v| v.to_enum::<Format>()), Some(Format::Yaml));
+/// ```
+pub trait ValueEnum: Sized {
+    /// Returns `Vec<&'static str>` with the choice names of the enum variants
+    fn choices() -> Vec<&'static str>;
+
+    /// Returns `Option<Self>` with the enum variant for the choice name `choice`.
+    /// Returns `None` if `choice` is not a choice name.
+    fn from_choice(choice: &str) -> Option<Self>;
+}
+
+/// A logging verbosity level that is mapped from the net occurrence count of a counted option (e.g., `-vv` or `-q`)
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::spec::Verbosity;
+///
+/// assert_eq!(Verbosity::from_count(2, Verbosity::Warn), Verbosity::Debug);
+/// assert_eq!(Verbosity::from_count(-1, Verbosity::Warn), Verbosity::Error);
+/// assert_eq!(Verbosity::from_count(9, Verbosity::Warn), Verbosity::Trace);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Verbosity {
+    /// No logging
+    Off,
+    /// Error level logging
+    Error,
+    /// Warning level logging
+    Warn,
+    /// Information level logging
+    Info,
+    /// Debug level logging
+    Debug,
+    /// Trace level logging
+    Trace,
+}
+
+impl Verbosity {
+    /// Returns the `Verbosity` that is `count` levels above the `base` level (or below it for a negative `count`), limited to the `Verbosity::Off` and `Verbosity::Trace` levels
+    pub fn from_count(count: i64, base: Verbosity) -> Verbosity {
+        let levels = [
+            Verbosity::Off,
+            Verbosity::Error,
+            Verbosity::Warn,
+            Verbosity::Info,
+            Verbosity::Debug,
+            Verbosity::Trace,
+        ];
+        let index = (base as i64).saturating_add(count);
+        levels[index.clamp(0, levels.len() as i64 - 1) as usize]
+    }
+}
+
+/// An option definition in a `Spec`
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::spec::OptionSpec;
+///
+/// let verbose = OptionSpec::flag("--verbose").short('v').help("Print verbose output");
+/// let output = OptionSpec::value("-o").long("output").value_name("PATH").required();
+///
+/// assert_eq!(verbose.key(), "--verbose");
+/// assert_eq!(output.key(), "--output");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionSpec {
+    /// The short option character (e.g., `o` for `-o`)
+    pub short: Option<char>,
+    /// The long option name without the leading double hyphen (e.g., `output` for `--output`)
+    pub long: Option<String>,
+    /// The kind of option argument that the option accepts
+    pub kind: OptionKind,
+    /// The name of the option value that is displayed in help text
+    pub value_name: String,
+    /// The option description that is displayed in help text
+    pub help: String,
+    /// `true` if the option must be used in the command
+    pub required: bool,
+    /// `true` if the long switch option has a `--no-` prefixed negation form (e.g., `--no-color` for `--color`)
+    pub negatable: bool,
+    /// The short or long option string that decrements the count of a counted option (e.g., `-q` for `-v`)
+    pub decrement: Option<String>,
+    /// The long option string that reads the option value from the file at the path in its value (e.g., `--token-file` for `--token`)
+    pub file_option: Option<String>,
+    /// `true` if an option value with an `@` prefix is read from the file at the path that follows the prefix (e.g., `--body=@body.json`)
+    pub at_file: bool,
+    /// The maximum size in bytes of a file that the option value is read from
+    pub max_file_size: u64,
+    /// The valid option values.  All values are valid when there are no choices.
+    pub choices: Vec<String>,
+    /// The alternate names of option value choices as (alias, choice) tuples
+    pub choice_aliases: Vec<(String, String)>,
+    /// `true` if option value choices and aliases are matched without regard to case
+    pub ignore_case: bool,
+    /// The option value validators and parsers in the order that they are run
+    pub validators: Vec<Validator>,
+    /// The character that delimits the items of an option value list (e.g., `,` for `--tags=a,b,c`)
+    pub delimiter: Option<char>,
+    /// The character that escapes a literal delimiter or escape character in an option value list item (e.g., `\` for `--tags=a\,b`)
+    pub escape: Option<char>,
+    /// `true` if empty items in an option value list are not valid (e.g., `--tags=a,,b`)
+    pub reject_empty: bool,
+    /// The policy for duplicate keys in the values of an `OptionKind::Map` option
+    pub duplicate_keys: DuplicateKeys,
+    /// The environment variable that defines the option value in a `ConfigLayers` parse
+    pub env: Option<String>,
+    /// `true` if the option value is redacted in `Command` display, debug, and serialized output and in parse error messages (e.g., `--password=****`)
+    pub sensitive: bool,
+}
+
+impl OptionSpec {
+    /// Instantiates and returns a new `OptionSpec` struct for a switch option that does not take a value
+    ///
+    /// `switch` is the short (e.g., `-v`) or long (e.g., `--verbose`) option string.
+    pub fn flag(switch: &str) -> Self {
+        OptionSpec::new(switch, OptionKind::Flag)
+    }
+
+    /// Instantiates and returns a new `OptionSpec` struct for an option with an optional value
+    ///
+    /// `switch` is the short (e.g., `-c`) or long (e.g., `--color`) option string.  `implicit` is the value that is used when the option is used without a value.
+    ///
+    /// # Remarks
+    /// The optional value must be in the same argument as the option (e.g., `--color=never` or `-cnever`).  The argument that follows the option is never used as its value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::spec::{OptionSpec, Spec};
+    ///
+    /// let spec = Spec::new("ls").option(OptionSpec::optional_value("--color", "always").value_name("WHEN"));
+    /// let argv: Vec<String> = vec!["ls", "--color", "never"].into_iter().map(String::from).collect();
+    /// let c = spec.parse(&argv).unwrap();
+    ///
+    /// assert_eq!(c.get_value("--color").and_then(|v| v.as_str()), Some("always"));
+    /// assert_eq!(c.get_argument_last().unwrap(), "never");
+    /// ```
+    pub fn optional_value(switch: &str, implicit: &str) -> Self {
+        OptionSpec::new(switch, OptionKind::OptionalValue(implicit.to_string()))
+    }
+
+    /// Instantiates and returns a new `OptionSpec` struct for a switch option that is counted each time that it is used
+    ///
+    /// `switch` is the short (e.g., `-v`) or long (e.g., `--verbose`) option string.
+    ///
+    /// # Remarks
+    /// The option value is a `Value::Count` with the net occurrence count.  Repeated options (`-v -v`), multi-option short syntax options (`-vv`), and long options (`--verbose`) are counted the same way.
+    pub fn count(switch: &str) -> Self {
+        OptionSpec::new(switch, OptionKind::Count)
+    }
+
+    /// Instantiates and returns a new `OptionSpec` struct for an option that requires a value
+    ///
+    /// `switch` is the short (e.g., `-o`) or long (e.g., `--output`) option string.
+    pub fn value(switch: &str) -> Self {
+        OptionSpec::new(switch, OptionKind::Value)
+    }
+
+    /// Instantiates and returns a new `OptionSpec` struct for an option that requires a `KEY=VALUE` value
+    ///
+    /// `switch` is the short (e.g., `-D`) or long (e.g., `--set`) option string.
+    ///
+    /// # Remarks
+    /// The option value is a `Value::Map` with the ordered entries of all of the option values.  The value is split at the first equal symbol.  Duplicate keys are handled with the `DuplicateKeys::LastWins` policy unless a policy is defined with `OptionSpec::duplicate_keys`.  Option value choices and validators are applied to the entry values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::spec::{OptionSpec, Spec};
+    ///
+    /// let spec = Spec::new("cc").option(OptionSpec::map("-D"));
+    /// let argv: Vec<String> = vec!["cc", "-DDEBUG=1", "-D", "NAME=a=b", "main.c"]
+    ///     .into_iter()
+    ///     .map(String::from)
+    ///     .collect();
+    /// let c = spec.parse(&argv).unwrap();
+    /// let defines = c.get_value("-D").unwrap();
+    ///
+    /// assert_eq!(defines.get("DEBUG"), Some("1"));
+    /// assert_eq!(defines.get("NAME"), Some("a=b"));
+    /// ```
+    pub fn map(switch: &str) -> Self {
+        OptionSpec::new(switch, OptionKind::Map).value_name("KEY=VALUE")
+    }
+
+    // Instantiates and returns a new `OptionSpec` struct for the option string `switch`
+    fn new(switch: &str, kind: OptionKind) -> Self {
+        let option = OptionSpec {
+            short: None,
+            long: None,
+            kind,
+            value_name: String::from("VALUE"),
+            help: String::new(),
+            required: false,
+            negatable: false,
+            decrement: None,
+            file_option: None,
+            at_file: false,
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            choices: Vec::new(),
+            choice_aliases: Vec::new(),
+            ignore_case: false,
+            validators: Vec::new(),
+            delimiter: None,
+            escape: None,
+            reject_empty: false,
+            duplicate_keys: DuplicateKeys::LastWins,
+            env: None,
+            sensitive: false,
+        };
+        if switch.starts_with("--") {
+            option.long(switch)
+        } else {
+            match switch.trim_start_matches('-').chars().next() {
+                Some(c) => option.short(c),
+                None => option,
+            }
+        }
+    }
+
+    /// Returns the `OptionSpec` with the short option character `short`
+    pub fn short(mut self, short: char) -> Self {
+        self.short = Some(short);
+        self
+    }
+
+    /// Returns the `OptionSpec` with the long option name `long`, with or without the leading double hyphen
+    pub fn long(mut self, long: &str) -> Self {
+        self.long = Some(long.trim_start_matches('-').to_string());
+        self
+    }
+
+    /// Returns the `OptionSpec` with the value name `value_name` that is displayed in help text
+    pub fn value_name(mut self, value_name: &str) -> Self {
+        self.value_name = value_name.to_string();
+        self
+    }
+
+    /// Returns the `OptionSpec` with the option description `help` that is displayed in help text
+    pub fn help(mut self, help: &str) -> Self {
+        self.help = help.to_string();
+        self
+    }
+
+    /// Returns the `OptionSpec` defined as an option that must be used in the command
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Returns the `OptionSpec` defined as a switch option with a `--no-` prefixed negation form (e.g., `--no-color` for `--color`)
+    ///
+    /// # Remarks
+    /// The option value is `Value::Flag(true)` when the option is used, `Value::Flag(false)` when the negation form is used, and is not defined when neither is used.  The last occurrence wins.
+    pub fn negatable(mut self) -> Self {
+        self.negatable = true;
+        self
+    }
+
+    /// Returns the counted `OptionSpec` with the short (e.g., `-q`) or long (e.g., `--quiet`) option string `switch` that decrements the count
+    pub fn decrement(mut self, switch: &str) -> Self {
+        self.decrement = Some(switch.to_string());
+        self
+    }
+
+    /// Returns the `OptionSpec` with the long option string `switch` (e.g., `--token-file`) that reads the option value from the file at the path in its value
+    ///
+    /// # Remarks
+    /// One trailing newline is removed from the file contents.  The file contents are validated as the option value.  Files that are larger than `OptionSpec.max_file_size` are not read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::spec::{OptionSpec, Spec};
+    ///
+    /// let spec = Spec::new("tool").option(OptionSpec::value("--token").file_option("--token-file"));
+    ///
+    /// assert_eq!(spec.find("--token-file").unwrap().key(), "--token");
+    /// // let c = spec.parse(&argv)?; // tool --token-file=token.txt
+    /// ```
+    pub fn file_option(mut self, switch: &str) -> Self {
+        self.file_option = Some(switch.to_string());
+        self
+    }
+
+    /// Returns the `OptionSpec` defined to read an option value with an `@` prefix from the file at the path that follows the prefix (e.g., `--body=@body.json`)
+    ///
+    /// # Remarks
+    /// A leading `@@` is an escape for a literal value that begins with `@` (e.g., `@@user` is the value `@user`).
+    pub fn at_file(mut self) -> Self {
+        self.at_file = true;
+        self
+    }
+
+    /// Returns the `OptionSpec` with the maximum size `bytes` of a file that the option value is read from
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = bytes;
+        self
+    }
+
+    /// Returns the `OptionSpec` with the valid option values in `choices`
+    ///
+    /// # Remarks
+    /// Option values are validated during parsing and the choices are listed in help text.
+    pub fn choices(mut self, choices: &[&str]) -> Self {
+        self.choices = choices.iter().map(|x| x.to_string()).collect();
+        self
+    }
+
+    /// Returns the `OptionSpec` with the valid option values that are defined by the `ValueEnum` type `T`
+    pub fn value_enum<T: ValueEnum>(self) -> Self {
+        self.choices(&T::choices())
+    }
+
+    /// Returns the `OptionSpec` with the alternate name `alias` for the option value choice `choice`
+    pub fn choice_alias(mut self, alias: &str, choice: &str) -> Self {
+        self.choice_aliases
+            .push((alias.to_string(), choice.to_string()));
+        self
+    }
+
+    /// Returns the `OptionSpec` defined to match option value choices and aliases without regard to case
+    pub fn ignore_case(mut self) -> Self {
+        self.ignore_case = true;
+        self
+    }
+
+    /// Returns the `OptionSpec` with the option value validator `validator` that returns a failure message for an invalid value
+    ///
+    /// # Remarks
+    /// Validators and parsers are run during parsing in the order that they are defined, after option value choices are resolved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::errors::ErrorKind;
+    /// use commandlines::spec::{OptionSpec, Spec};
+    ///
+    /// let spec = Spec::new("server").option(OptionSpec::value("--port").validator(|x| {
+    ///     match x.parse::<u32>() {
+    ///         Ok(1..=65535) => Ok(()),
+    ///         _ => Err(String::from("must be a port number in the range 1-65535")),
+    ///     }
+    /// }));
+    /// let argv: Vec<String> = vec!["server", "--port", "0"].into_iter().map(String::from).collect();
+    /// let error = spec.parse(&argv).unwrap_err();
+    ///
+    /// assert_eq!(error.span.unwrap().index, 2);
+    /// match error.kind {
+    ///     ErrorKind::InvalidValue(option, value, _) => assert_eq!((&option[..], &value[..]), ("--port", "0")),
+    ///     _ => panic!("unexpected error"),
+    /// }
+    /// ```
+    pub fn validator<F>(self, validator: F) -> Self
+    where
+        F: Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.parser(move |x| validator(x).map(|_| x.to_string()))
+    }
+
+    /// Returns the `OptionSpec` with the option value parser `parser` that returns the parsed option value (e.g., a normalized value) or a failure message for an invalid value
+    pub fn parser<F>(mut self, parser: F) -> Self
+    where
+        F: Fn(&str) -> Result<String, String> + Send + Sync + 'static,
+    {
+        self.validators.push(Validator(Arc::new(parser)));
+        self
+    }
+
+    /// Returns the `OptionSpec` with an option value validator for values that can be parsed as the `FromStr` type `T`
+    ///
+    /// # Remarks
+    /// Use `Value::parse` to parse the option value as `T`.
+    pub fn value_type<T>(self) -> Self
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.validator(|x| x.parse::<T>().map(|_| ()).map_err(|e| e.to_string()))
+    }
+
+    /// Returns the `OptionSpec` with the option value list item delimiter `delimiter`
+    ///
+    /// # Remarks
+    /// The option value is a `Value::List` with the items of all of the option values in the order that they are used (e.g., `a`, `b`, `c` for `--tags=a,b --tags c`).  Option value choices and validators are applied to each item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::spec::{OptionSpec, Spec};
+    ///
+    /// let spec = Spec::new("tool").option(OptionSpec::value("--tags").delimiter(',').escape('\\'));
+    /// let argv: Vec<String> = vec!["tool", "--tags=a,b\\,c", "--tags", "d"]
+    ///     .into_iter()
+    ///     .map(String::from)
+    ///     .collect();
+    /// let c = spec.parse(&argv).unwrap();
+    ///
+    /// assert_eq!(c.get_value("--tags").and_then(|v| v.as_list()).unwrap(), ["a", "b,c", "d"]);
+    /// ```
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = Some(delimiter);
+        self
+    }
+
+    /// Returns the `OptionSpec` with the option value list item escape character `escape`
+    pub fn escape(mut self, escape: char) -> Self {
+        self.escape = Some(escape);
+        self
+    }
+
+    /// Returns the `OptionSpec` defined to reject empty option value list items
+    pub fn reject_empty(mut self) -> Self {
+        self.reject_empty = true;
+        self
+    }
+
+    /// Returns the map `OptionSpec` with the duplicate key policy `policy`
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Returns the `OptionSpec` with the environment variable `name` that defines the option value in a `ConfigLayers` parse
+    pub fn env(mut self, name: &str) -> Self {
+        self.env = Some(name.to_string());
+        self
+    }
+
+    /// Returns the `OptionSpec` defined with a sensitive value (e.g., a password) that is redacted in output
+    ///
+    /// # Remarks
+    /// The value remains available in `Command.argv` and `Command.values`.  Use `Command::redacted` or `Spec::redact_argv` to get the redacted arguments.
+    pub fn sensitive(mut self) -> Self {
+        self.sensitive = true;
+        self
+    }
+
+    /// Returns `Vec<String>` with the option value list items in `value`.  The `value` is returned as a single item when the option does not have a delimiter.
+    pub fn split_list(&self, value: &str) -> Vec<String> {
+        let delimiter = match self.delimiter {
+            Some(x) => x,
+            None => return vec![value.to_string()],
+        };
+        let mut items = Vec::new();
+        let mut item = String::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            if Some(c) == self.escape {
+                // a trailing escape character is a literal character
+                item.push(chars.next().unwrap_or(c));
+            } else if c == delimiter {
+                items.push(item);
+                item = String::new();
+            } else {
+                item.push(c);
+            }
+        }
+        items.push(item);
+        items
+    }
+
+    /// Returns `String` with the option value list items in `items` joined with the delimiter.  Delimiter and escape characters in the items are escaped when the option has an escape character.
+    pub fn join_list(&self, items: &[&str]) -> String {
+        let delimiter = match self.delimiter {
+            Some(x) => x,
+            None => return items.concat(),
+        };
+        let escaped: Vec<String> = items
+            .iter()
+            .map(|item| match self.escape {
+                Some(escape) => item.chars().fold(String::new(), |mut acc, c| {
+                    if c == delimiter || c == escape {
+                        acc.push(escape);
+                    }
+                    acc.push(c);
+                    acc
+                }),
+                None => item.to_string(),
+            })
+            .collect();
+        escaped.join(&delimiter.to_string())
+    }
+
+    /// Returns the `OptionSpec` with an option value parser that resolves path values with `path` (e.g., `~` expansion, normalization, and file system checks)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::spec::{OptionSpec, Spec};
+    /// use commandlines::utilities::path::{PathCheck, PathValue};
+    /// use std::path::Path;
+    ///
+    /// let spec = Spec::new("tool").option(
+    ///     OptionSpec::value("--config").path_value(PathValue::new().cwd(Path::new("/etc")).check(PathCheck::MustBeDir)),
+    /// );
+    /// let argv: Vec<String> = vec!["tool", "--config=../etc/"].into_iter().map(String::from).collect();
+    /// let c = spec.parse(&argv).unwrap();
+    ///
+    /// assert_eq!(c.get_value("--config").and_then(|v| v.as_str()), Some("/etc"));
+    /// ```
+    pub fn path_value(self, path: PathValue) -> Self {
+        self.parser(move |x| {
+            path.resolve(x)
+                .map(|resolved| resolved.to_string_lossy().into_owned())
+        })
+    }
+
+    /// Returns `Option<String>` with the option value choice for `value`, or `value` when there are no choices.
+    /// Returns `None` if `value` is not a choice or an alias of a choice.
+    pub fn resolve_choice(&self, value: &str) -> Option<String> {
+        if self.choices.is_empty() {
+            return Some(value.to_string());
+        }
+        let eq = |x: &str| {
+            if self.ignore_case {
+                x.to_lowercase() == value.to_lowercase()
+            } else {
+                x == value
+            }
+        };
+        match self.choices.iter().find(|x| eq(x)) {
+            Some(x) => Some(x.clone()),
+            None => self
+                .choice_aliases
+                .iter()
+                .find(|x| eq(&x.0))
+                .map(|x| x.1.clone()),
+        }
+    }
+
+    /// Returns `Vec<String>` with the option value choices that begin with `prefix` for command line completion
+    pub fn complete_value(&self, prefix: &str) -> Vec<String> {
+        self.choices
+            .iter()
+            .filter(|x| {
+                if self.ignore_case {
+                    x.to_lowercase().starts_with(&prefix.to_lowercase())
+                } else {
+                    x.starts_with(prefix)
+                }
+            })
+            .cloned()
+            .collect()
+    }
+
+    // Returns the option description with the option value choices and the environment variable
+    // that are displayed in help text
+    fn description(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        if !self.help.is_empty() {
+            parts.push(self.help.clone());
+        }
+        if !self.choices.is_empty() {
+            parts.push(format!("[possible values: {}]", self.choices.join(", ")));
+        }
+        if let Some(name) = &self.env {
+            parts.push(format!("[env: {}]", name));
+        }
+        parts.join(" ")
+    }
+
+    /// Returns `String` with the key of the option in `Command.values`.  This is the long option string if a long option is defined and the short option string if it is not (e.g., `--output` or `-o`).
+    pub fn key(&self) -> String {
+        match (&self.long, self.short) {
+            (Some(long), _) => format!("--{}", long),
+            (None, Some(short)) => format!("-{}", short),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// Returns boolean for the question "Does the option require a value?"
+    pub fn takes_value(&self) -> bool {
+        self.kind == OptionKind::Value || self.kind == OptionKind::Map
+    }
+
+    /// Returns boolean for the question "Does the option have an optional value?"
+    pub fn takes_optional_value(&self) -> bool {
+        matches!(self.kind, OptionKind::OptionalValue(_))
+    }
+
+    /// Returns boolean for the question "Is `switch` the short or long option string of the option?"
+    pub fn matches(&self, switch: &str) -> bool {
+        if self.decrements(switch) || self.reads_file(switch) {
+            return true;
+        }
+        if let Some(long) = switch.strip_prefix("--") {
+            return self.long.as_ref().map(|x| &x[..]) == Some(long) || self.negates(switch);
+        }
+        let mut chars = switch.chars();
+        chars.next() == Some('-') && chars.next() == self.short && chars.next().is_none()
+    }
+
+    /// Returns boolean for the question "Is `switch` the negation form of the option (e.g., `--no-color`)?"
+    pub fn negates(&self, switch: &str) -> bool {
+        self.negatable
+            && self.long.is_some()
+            && switch.strip_prefix("--no-") == self.long.as_ref().map(|x| &x[..])
+    }
+
+    /// Returns boolean for the question "Is `switch` the option string that decrements the count of the option (e.g., `-q`)?"
+    pub fn decrements(&self, switch: &str) -> bool {
+        self.kind == OptionKind::Count && self.decrement.as_ref().map(|x| &x[..]) == Some(switch)
+    }
+
+    /// Returns boolean for the question "Is `switch` the option string that reads the option value from a file (e.g., `--token-file`)?"
+    pub fn reads_file(&self, switch: &str) -> bool {
+        self.file_option.as_ref().map(|x| &x[..]) == Some(switch)
+    }
+
+    // Returns the option switches and value name that are displayed in help text (e.g., `-o, --output <PATH>`)
+    fn synopsis(&self) -> String {
+        let mut synopsis = match (self.short, &self.long) {
+            (Some(short), Some(long)) if self.negatable => format!("-{}, --[no-]{}", short, long),
+            (None, Some(long)) if self.negatable => format!("    --[no-]{}", long),
+            (Some(short), Some(long)) => format!("-{}, --{}", short, long),
+            (None, Some(long)) => format!("    --{}", long),
+            (Some(short), None) => format!("-{}", short),
+            (None, None) => String::new(),
+        };
+        if let Some(decrement) = &self.decrement {
+            synopsis.push_str(&format!(" | {}", decrement));
+        }
+        if let (true, Some(delimiter)) = (self.takes_value(), self.delimiter) {
+            synopsis.push_str(&format!(" <{}>[{}...]", self.value_name, delimiter));
+        } else if self.takes_value() {
+            synopsis.push_str(&format!(" <{}>", self.value_name));
+        } else if self.takes_optional_value() {
+            match self.long {
+                Some(_) => synopsis.push_str(&format!("[={}]", self.value_name)),
+                None => synopsis.push_str(&format!("[{}]", self.value_name)),
+            }
+        }
+        if let Some(file_option) = &self.file_option {
+            synopsis.push_str(&format!(" | {} <FILE>", file_option));
+        }
+        synopsis
+    }
+}
+
+/// A command line option specification that is used to parse and validate a command
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::spec::{OptionSpec, Spec};
+///
+/// let spec = Spec::new("tool")
+///     .option(OptionSpec::flag("--verbose").short('v'))
+///     .option(OptionSpec::value("--output").short('o').value_name("PATH"));
+///
+/// let argv: Vec<String> = vec!["tool", "-vo", "out.txt", "file"]
+///     .into_iter()
+///     .map(String::from)
+///     .collect();
+///
+/// match spec.parse(&argv) {
+///     Ok(c) => assert_eq!(c.get_value("--output").and_then(|v| v.as_str()), Some("out.txt")),
+///     Err(e) => eprintln!("{}", spec.render_error(&argv, &e)),
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spec {
+    /// The executable name
+    pub name: String,
+    /// The option definitions
+    pub options: Vec<OptionSpec>,
+    /// The option parsing order
+    pub order: ParseOrder,
+    /// The name of the trailing variable argument positional that captures the first positional argument and all of the arguments that follow it (e.g., `CMD` in `mytool --fast cargo build --release`)
+    pub trailing_var_arg: Option<String>,
+}
+
+impl Spec {
+    /// Instantiates and returns a new `Spec` struct for the executable `name`
+    pub fn new(name: &str) -> Self {
+        Spec {
+            name: name.to_string(),
+            options: Vec::new(),
+            order: ParseOrder::Permute,
+            trailing_var_arg: None,
+        }
+    }
+
+    /// Returns the `Spec` with the option definition `option`
+    pub fn option(mut self, option: OptionSpec) -> Self {
+        self.options.push(option);
+        self
+    }
+
+    /// Returns the `Spec` with the option parsing order `order`
+    pub fn order(mut self, order: ParseOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Returns the `Spec` with the trailing variable argument positional `name` that captures the first positional argument and all of the arguments that follow it, including options
+    ///
+    /// # Remarks
+    /// Option parsing ends at the first positional argument, as with `parsers::ParseOrder::RequireOrder`.  The captured arguments are defined in `Command.remainder_argv` and as a `Value::List` with the key `name` in `Command.values`.  Use `Command::get_trailing_command` to execute the captured arguments as a child process.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::spec::{OptionSpec, Spec};
+    ///
+    /// let spec = Spec::new("mytool")
+    ///     .option(OptionSpec::flag("--fast"))
+    ///     .trailing_var_arg("CMD");
+    /// let argv: Vec<String> = vec!["mytool", "--fast", "cargo", "build", "--release"]
+    ///     .into_iter()
+    ///     .map(String::from)
+    ///     .collect();
+    /// let c = spec.parse(&argv).unwrap();
+    ///
+    /// assert_eq!(
+    ///     c.get_value("CMD").and_then(|v| v.as_list()),
+    ///     Some(&["cargo".to_string(), "build".to_string(), "--release".to_string()][..])
+    /// );
+    /// assert_eq!(c.get_trailing_command().unwrap().get_program(), "cargo");
+    /// ```
+    pub fn trailing_var_arg(mut self, name: &str) -> Self {
+        self.trailing_var_arg = Some(name.to_string());
+        self
+    }
+
+    /// Returns `String` with the help text for the command options
+    ///
+    /// # Remarks
+    /// Negatable options are displayed once with both forms (e.g., `--[no-]color`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::spec::{OptionSpec, Spec};
+    ///
+    /// let spec = Spec::new("tool")
+    ///     .option(OptionSpec::flag("--color").negatable().help("Colorize output"))
+    ///     .option(OptionSpec::value("-o").long("output").value_name("PATH").help("Output path"));
+    ///
+    /// assert_eq!(
+    ///     spec.help(),
+    ///     "Usage: tool [OPTIONS]\n\nOptions:\n      --[no-]color     Colorize output\n  -o, --output <PATH>  Output path\n"
+    /// );
+    /// ```
+    pub fn help(&self) -> String {
+        let mut help = format!("Usage: {} [OPTIONS]", self.name);
+        if let Some(name) = &self.trailing_var_arg {
+            help.push_str(&format!(" [{}]...", name));
+        }
+        help.push('\n');
+        if self.options.is_empty() {
+            return help;
+        }
+        let synopses: Vec<String> = self.options.iter().map(|x| x.synopsis()).collect();
+        let width = synopses
+            .iter()
+            .map(|x| x.chars().count())
+            .max()
+            .unwrap_or(0);
+        help.push_str("\nOptions:\n");
+        for (option, synopsis) in self.options.iter().zip(synopses) {
+            let line = format!(
+                "  {:width$}  {}",
+                synopsis,
+                option.description(),
+                width = width
+            );
+            help.push_str(line.trim_end());
+            help.push('\n');
+        }
+        help
+    }
+
+    /// Returns `Option<&OptionSpec>` with the option definition for the short or long option string `switch` (e.g., `-o` or `--output`).
+    /// Returns `None` if the option is not defined.
+    pub fn find(&self, switch: &str) -> Option<&OptionSpec> {
+        self.options.iter().find(|x| x.matches(switch))
+    }
+
+    /// Returns `Vec<String>` with the option value choices of the option `switch` (e.g., `--format`) that begin with `prefix` for command line completion.
+    /// Returns an empty `Vec` if the option is not defined or does not have choices.
+    pub fn complete_value(&self, switch: &str, prefix: &str) -> Vec<String> {
+        match self.find(switch) {
+            Some(option) => option.complete_value(prefix),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns `Result<Command, ParseError>` with a `Command` that is parsed from the command line arguments in `argv` and validated against the specification
+    ///
+    /// # Remarks
+    /// The parsed option values are defined in `Command.values`.  The `ParseError` locates the first argument that is not valid in the specification.
+    pub fn parse(&self, argv: &[String]) -> Result<Command, ParseError> {
+        let (tokens, values) = self.parse_argv(argv)?;
+        self.check_required(&values)?;
+        let sources = values
+            .keys()
+            .map(|x| (x.clone(), ValueSource::Argv))
+            .collect();
+
+        Ok(self.command(argv, tokens, values, sources))
+    }
+
+    /// Returns `Vec<String>` with the command line arguments in `argv` with the values of sensitive options replaced with `REDACTED`
+    ///
+    /// # Remarks
+    /// The arguments do not need to be valid in the specification.  Use `Spec::render_error` to render a `ParseError` with the redacted arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::spec::{OptionSpec, Spec};
+    ///
+    /// let spec = Spec::new("tool")
+    ///     .option(OptionSpec::value("--password").short('p').sensitive())
+    ///     .option(OptionSpec::flag("-v"));
+    /// let argv: Vec<String> = vec!["tool", "--password=hunter2", "-vp", "hunter2"]
+    ///     .into_iter()
+    ///     .map(String::from)
+    ///     .collect();
+    ///
+    /// assert_eq!(spec.redact_argv(&argv), vec!["tool", "--password=****", "-vp", "****"]);
+    /// ```
+    pub fn redact_argv(&self, argv: &[String]) -> Vec<String> {
+        redact_spans(argv, &self.sensitive_spans(&self.tokenize(argv)))
+    }
+
+    /// Returns `String` with the `error` message followed by the command line in `argv` with the values of sensitive options replaced with `REDACTED`, and a caret line that marks the location of the argument that caused the error
+    ///
+    /// # Remarks
+    /// The location of the error is moved to the same text in the redacted command line (see `errors::render_redacted_error`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::spec::{OptionSpec, Spec};
+    ///
+    /// let spec = Spec::new("tool").option(OptionSpec::value("--password").sensitive().choices(&["a", "b"]));
+    /// let argv: Vec<String> = vec!["tool", "--password=hunter2"].into_iter().map(String::from).collect();
+    /// let e = spec.parse(&argv).unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     spec.render_error(&argv, &e),
+    ///     "error: invalid value '****' for option '--password' (possible values: a, b)\n  tool --password=****\n                  ^^^^"
+    /// );
+    /// ```
+    pub fn render_error(&self, argv: &[String], error: &ParseError) -> String {
+        errors::render_redacted_error(argv, &self.sensitive_spans(&self.tokenize(argv)), error)
+    }
+
+    /// Returns `Vec<Token>` with one classified `Token` for each argument in `argv` that is parsed with the option definitions and the option parsing order
+    ///
+    /// # Remarks
+    /// The separate values of options that take a value are classified as `TokenKind::OptionValue`.  The arguments are tokenized with `parsers::ParseOrder::RequireOrder` when a trailing variable argument positional is defined.
+    pub fn tokenize(&self, argv: &[String]) -> Vec<Token> {
+        let order = match self.trailing_var_arg {
+            Some(_) => ParseOrder::RequireOrder,
+            None => self.order,
+        };
+        parsers::tokenize_with_values(argv, order, |token| self.consumes_value(token))
+    }
+
+    // Returns the `Command` with the parsed option values and value sources of `argv`
+    fn command(
+        &self,
+        argv: &[String],
+        tokens: Vec<Token>,
+        values: HashMap<String, Value>,
+        sources: HashMap<String, ValueSource>,
+    ) -> Command {
+        let spans = self.sensitive_spans(&tokens);
+        let mut command = Command::new_with_tokens(argv.to_vec(), tokens);
+        command.values = values;
+        command.value_sources = sources;
+        command.sensitive_spans = spans;
+        command.sensitive_options = self
+            .options
+            .iter()
+            .filter(|x| x.sensitive)
+            .map(|x| x.key())
+            .collect();
+        command
+    }
+
+    // Returns the locations of the values of sensitive options in the token stream `tokens`
+    fn sensitive_spans(&self, tokens: &[Token]) -> Vec<Span> {
+        let mut spans = Vec::new();
+        for (i, token) in tokens.iter().enumerate() {
+            let separated = match tokens.get(i + 1) {
+                Some(x) if x.kind == TokenKind::OptionValue => Some(x),
+                _ => None,
+            };
+            match token.kind {
+                TokenKind::LongFlag | TokenKind::LongWithValue => {
+                    let option = match token.option().and_then(|x| self.find(x)) {
+                        Some(x) if x.sensitive => x,
+                        _ => continue,
+                    };
+                    match (token.definition_span(), separated) {
+                        (Some(span), _) => spans.push(span),
+                        (None, Some(x)) if option.takes_value() => spans.push(whole_span(x)),
+                        (_, _) => {}
+                    }
+                }
+                TokenKind::ShortFlag | TokenKind::ShortCluster | TokenKind::ShortWithValue => {
+                    for (start, c) in token.text.char_indices().skip(1) {
+                        let option = match self.find(&format!("-{}", c)) {
+                            Some(x) => x,
+                            None => break,
+                        };
+                        if !option.takes_value() && !option.takes_optional_value() {
+                            continue;
+                        }
+                        let rest = &token.text[start + c.len_utf8()..];
+                        if !option.sensitive {
+                            break;
+                        } else if !rest.is_empty() {
+                            let value = rest.strip_prefix('=').unwrap_or(rest);
+                            spans.push(Span {
+                                index: token.index,
+                                start: token.text.len() - value.len(),
+                                end: token.text.len(),
+                            });
+                        } else if let Some(x) = separated {
+                            spans.push(whole_span(x));
+                        }
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        spans
+    }
+
+    // Returns `error` with the value of a sensitive option replaced with `REDACTED`
+    fn redact_error(&self, error: ParseError) -> ParseError {
+        let sensitive = |option: &str| self.find(option).is_some_and(|x| x.sensitive);
+        let kind = match error.kind {
+            ErrorKind::InvalidChoice(option, _, choices) if sensitive(&option) => {
+                ErrorKind::InvalidChoice(option, REDACTED.to_string(), choices)
+            }
+            ErrorKind::InvalidValue(option, _, message) if sensitive(&option) => {
+                ErrorKind::InvalidValue(option, REDACTED.to_string(), message)
+            }
+            kind => kind,
+        };
+        ParseError::new(kind, error.span)
+    }
+
+    // Returns the token stream and the parsed option values of `argv` without the required option check
+    fn parse_argv(
+        &self,
+        argv: &[String],
+    ) -> Result<(Vec<Token>, HashMap<String, Value>), ParseError> {
+        let tokens = self.tokenize(argv);
+        let mut values = self
+            .parse_values(&tokens, true)
+            .map_err(|e| self.redact_error(e))?;
+        if let Some(name) = &self.trailing_var_arg {
+            if let Some(remainder) = parsers::remainder_args_from_tokens(&tokens) {
+                values.insert(name.clone(), Value::List(remainder));
+            }
+        }
+        Ok((tokens, values))
+    }
+
+    // Returns a parse error if `argv` is not valid in the specification, without reading option values from files
+    fn check_argv(&self, argv: &[String]) -> Result<(), ParseError> {
+        let values = self
+            .parse_values(&self.tokenize(argv), false)
+            .map_err(|e| self.redact_error(e))?;
+        self.check_required(&values)
+    }
+
+    // Returns a missing required option error if a required option is not defined in `values`
+    fn check_required(&self, values: &HashMap<String, Value>) -> Result<(), ParseError> {
+        for option in self.options.iter().filter(|x| x.required) {
+            if !values.contains_key(&option.key()) {
+                return Err(ParseError::new(
+                    ErrorKind::MissingRequired(option.key()),
+                    None,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Returns boolean for the question "Does the option token require the argument that follows it as its value?"
+    fn consumes_value(&self, token: &Token) -> bool {
+        match token.kind {
+            TokenKind::LongFlag => self
+                .find(&token.text)
+                .is_some_and(|x| x.takes_value() || x.reads_file(&token.text)),
+            TokenKind::ShortFlag | TokenKind::ShortCluster => {
+                for (switch, span) in token.short_options() {
+                    let option = match self.find(&switch) {
+                        Some(x) => x,
+                        None => continue,
+                    };
+                    // an optional value option takes the rest of the argument and never the next argument
+                    if option.takes_optional_value() {
+                        return false;
+                    }
+                    if option.takes_value() {
+                        // the value is separated when the option is the last switch in the argument
+                        return span.end == token.text.len();
+                    }
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+
+    // Returns the parsed option values in the token stream `tokens`.  The values of file options and `@path`
+    // values are not read and not parsed when `read_files` is false.
+    fn parse_values(
+        &self,
+        tokens: &[Token],
+        read_files: bool,
+    ) -> Result<HashMap<String, Value>, ParseError> {
+        let mut values: HashMap<String, Value> = HashMap::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = &tokens[i];
+            let separated = match tokens.get(i + 1) {
+                Some(x) if x.kind == TokenKind::OptionValue => Some(x),
+                _ => None,
+            };
+            match token.kind {
+                TokenKind::LongFlag | TokenKind::LongWithValue => {
+                    let switch = token.option().unwrap_or("");
+                    let option = self.find_or_error(switch, token.option_span())?;
+                    let value = match (token.definition(), token.definition_span(), separated) {
+                        (Some(x), Some(span), _) => Some((x.to_string(), span)),
+                        (None, _, Some(x)) if option.takes_value() => {
+                            i += 1;
+                            Some((x.text.clone(), whole_span(x)))
+                        }
+                        (_, _, _) => None,
+                    };
+                    let span = token.option_span();
+                    insert_value(option, switch, value, span, read_files, &mut values)?;
+                }
+                TokenKind::ShortFlag | TokenKind::ShortCluster | TokenKind::ShortWithValue => {
+                    // the name of the previous switch option in the argument
+                    let mut previous: Option<String> = None;
+                    for (start, c) in token.text.char_indices().skip(1) {
+                        let span = Span {
+                            index: token.index,
+                            start,
+                            end: start + c.len_utf8(),
+                        };
+                        let switch = format!("-{}", c);
+                        if c == '=' {
+                            if let Some(x) = previous {
+                                let mut value_span = span;
+                                value_span.end = token.text.len();
+                                return Err(ParseError::new(
+                                    ErrorKind::UnexpectedValue(x),
+                                    Some(value_span),
+                                ));
+                            }
+                        }
+                        let option = self.find_or_error(&switch, Some(span))?;
+                        if option.takes_value() || option.takes_optional_value() {
+                            let rest = &token.text[span.end..];
+                            let value = if !rest.is_empty() {
+                                let value = rest.strip_prefix('=').unwrap_or(rest);
+                                let value_span = Span {
+                                    index: token.index,
+                                    start: token.text.len() - value.len(),
+                                    end: token.text.len(),
+                                };
+                                Some((value.to_string(), value_span))
+                            } else if let Some(x) = separated {
+                                i += 1;
+                                Some((x.text.clone(), whole_span(x)))
+                            } else {
+                                None
+                            };
+                            let span = Some(span);
+                            insert_value(option, &switch, value, span, read_files, &mut values)?;
+                            break;
+                        }
+                        insert_value(option, &switch, None, Some(span), read_files, &mut values)?;
+                        previous = if option.decrements(&switch) {
+                            Some(switch)
+                        } else {
+                            Some(option.key())
+                        };
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+
+        Ok(values)
+    }
+
+    // Returns the option definition for `switch` or an invalid option error at `span`
+    fn find_or_error(&self, switch: &str, span: Option<Span>) -> Result<&OptionSpec, ParseError> {
+        self.find(switch)
+            .ok_or_else(|| ParseError::new(ErrorKind::InvalidOption(switch.to_string()), span))
+    }
+}
+
+/// Returns `Vec<String>` with the command line arguments in `argv` with the text at each location in `spans` replaced with `REDACTED`
+///
+/// # Remarks
+/// Locations that are not in `argv` are ignored.
+pub fn redact_spans(argv: &[String], spans: &[Span]) -> Vec<String> {
+    let mut redacted = argv.to_vec();
+    for span in spans {
+        if let Some(arg) = redacted.get_mut(span.index) {
+            if arg.get(span.start..span.end).is_some_and(|x| !x.is_empty()) {
+                arg.replace_range(span.start..span.end, REDACTED);
+            }
+        }
+    }
+    redacted
+}
+
+// Returns the location of the whole argument of `token`
+fn whole_span(token: &Token) -> Span {
+    Span {
+        index: token.index,
+        start: 0,
+        end: token.text.len(),
+    }
+}
+
+// Inserts the parsed `value` and value location of the option string `switch` at `span` for `option` in `values`
+fn insert_value(
+    option: &OptionSpec,
+    switch: &str,
+    value: Option<(String, Span)>,
+    span: Option<Span>,
+    read_files: bool,
+    values: &mut HashMap<String, Value>,
+) -> Result<(), ParseError> {
+    let value = match value {
+        Some((x, _))
+            if !read_files
+                && !matches!(option.kind, OptionKind::Flag | OptionKind::Count)
+                && is_file_value(option, switch, &x) =>
+        {
+            // the unread file path is defined as the option value
+            let unread = match option.kind {
+                OptionKind::Map => Value::Map(Vec::new()),
+                _ => Value::Single(x),
+            };
+            values.entry(option.key()).or_insert(unread);
+            return Ok(());
+        }
+        Some((x, value_span)) => Some((file_value(option, switch, x, value_span)?, value_span)),
+        None if option.reads_file(switch) => {
+            return Err(ParseError::new(
+                ErrorKind::MissingValue(switch.to_string()),
+                span,
+            ))
+        }
+        None => None,
+    };
+    let alternate = option.negates(switch) || option.decrements(switch);
+    let parsed = match (&option.kind, value) {
+        (OptionKind::Flag, None) => Value::Flag(!option.negates(switch)),
+        (OptionKind::Count, None) => {
+            let count = values.get(&option.key()).and_then(|x| x.as_count());
+            let delta = if option.decrements(switch) { -1 } else { 1 };
+            Value::Count(count.unwrap_or(0) + delta)
+        }
+        (OptionKind::Flag, Some(_)) | (OptionKind::Count, Some(_)) => {
+            let name = if alternate {
+                switch.to_string()
+            } else {
+                option.key()
+            };
+            return Err(ParseError::new(ErrorKind::UnexpectedValue(name), span));
+        }
+        (OptionKind::Value, Some((x, value_span)))
+        | (OptionKind::OptionalValue(_), Some((x, value_span))) => {
+            option_value(option, x, Some(value_span), values)?
+        }
+        (OptionKind::OptionalValue(implicit), None) => {
+            option_value(option, implicit.clone(), span, values)?
+        }
+        (OptionKind::Map, Some((x, value_span))) => map_value(option, x, Some(value_span), values)?,
+        (OptionKind::Value, None) | (OptionKind::Map, None) => {
+            return Err(ParseError::new(ErrorKind::MissingValue(option.key()), span))
+        }
+    };
+    values.insert(option.key(), parsed);
+
+    Ok(())
+}
+
+// Returns boolean for the question "Is the option `value` read from a file?"
+fn is_file_value(option: &OptionSpec, switch: &str, value: &str) -> bool {
+    option.reads_file(switch)
+        || (option.at_file && value.len() > 1 && value.starts_with('@') && !value.starts_with("@@"))
+}
+
+// Returns the option value that is read from a file when `switch` is the file option string or the
+// value has an `@` prefix, or an invalid value error at `span` when the file cannot be read
+fn file_value(
+    option: &OptionSpec,
+    switch: &str,
+    value: String,
+    span: Span,
+) -> Result<String, ParseError> {
+    let path = if option.reads_file(switch) {
+        value.clone()
+    } else if option.at_file && value.starts_with("@@") {
+        return Ok(value[1..].to_string());
+    } else if option.at_file && value.len() > 1 && value.starts_with('@') {
+        value[1..].to_string()
+    } else {
+        return Ok(value);
+    };
+    read_value_file(Path::new(&path), option.max_file_size).map_err(|message| {
+        ParseError::new(
+            ErrorKind::InvalidValue(option.key(), value, message),
+            Some(span),
+        )
+    })
+}
+
+// Returns the contents of the file at `path` without one trailing newline, or a failure message
+// when the file cannot be read or is larger than `max_size` bytes
+fn read_value_file(path: &Path, max_size: u64) -> Result<String, String> {
+    let error = |e: String| format!("cannot read file '{}': {}", path.display(), e);
+    if path.is_dir() {
+        return Err(error(String::from("the path is a directory")));
+    }
+    let file = fs::File::open(path).map_err(|e| error(e.to_string()))?;
+    let mut text = String::new();
+    // the limit is exceeded when more than `max_size` bytes can be read
+    file.take(max_size.saturating_add(1))
+        .read_to_string(&mut text)
+        .map_err(|e| error(e.to_string()))?;
+    if text.len() as u64 > max_size {
+        return Err(format!(
+            "file '{}' exceeds the size limit of {} bytes",
+            path.display(),
+            max_size
+        ));
+    }
+    if text.ends_with('\n') {
+        text.pop();
+        if text.ends_with('\r') {
+            text.pop();
+        }
+    }
+
+    Ok(text)
+}
+
+// Returns the `Value::Map` option value with the `KEY=VALUE` entry in `value` merged into the
+// previous entries in `values` with the option duplicate key policy
+fn map_value(
+    option: &OptionSpec,
+    value: String,
+    span: Option<Span>,
+    values: &HashMap<String, Value>,
+) -> Result<Value, ParseError> {
+    let invalid = |message: String| {
+        ParseError::new(
+            ErrorKind::InvalidValue(option.key(), value.clone(), message),
+            span,
+        )
+    };
+    let (key, entry) = match value.split_once('=') {
+        Some((key, entry)) if !key.is_empty() => (key.to_string(), entry.to_string()),
+        _ => return Err(invalid(String::from("expected KEY=VALUE"))),
+    };
+    let entry = resolve_value(option, entry, span)?;
+    let mut map = match values.get(&option.key()) {
+        Some(Value::Map(x)) => x.clone(),
+        _ => Vec::new(),
+    };
+    match (option.duplicate_keys, map.iter().position(|x| x.0 == key)) {
+        (DuplicateKeys::Error, Some(_)) => {
+            return Err(invalid(format!("duplicate key '{}'", key)));
+        }
+        (DuplicateKeys::LastWins, Some(i)) => map[i].1 = entry,
+        (_, _) => map.push((key, entry)),
+    }
+
+    Ok(Value::Map(map))
+}
+
+// Returns the `Value::Single` option value for `value`, or the `Value::List` option value with
+// the items in `value` merged into the previous items in `values` when the option has a delimiter
+fn option_value(
+    option: &OptionSpec,
+    value: String,
+    span: Option<Span>,
+    values: &HashMap<String, Value>,
+) -> Result<Value, ParseError> {
+    if option.delimiter.is_none() {
+        return Ok(Value::Single(resolve_value(option, value, span)?));
+    }
+    let mut list = match values.get(&option.key()) {
+        Some(Value::List(x)) => x.clone(),
+        _ => Vec::new(),
+    };
+    for item in option.split_list(&value) {
+        if item.is_empty() && option.reject_empty {
+            return Err(ParseError::new(
+                ErrorKind::InvalidValue(
+                    option.key(),
+                    value,
+                    String::from("empty list items are not allowed"),
+                ),
+                span,
+            ));
+        }
+        list.push(resolve_value(option, item, span)?);
+    }
+
+    Ok(Value::List(list))
+}
+
+// Returns the option value for `value` after the option value choice is resolved and the
+// validators are run, or an invalid choice or invalid value error at `span`
+fn resolve_value(
+    option: &OptionSpec,
+    value: String,
+    span: Option<Span>,
+) -> Result<String, ParseError> {
+    let mut resolved = match option.resolve_choice(&value) {
+        Some(x) => x,
+        None => {
+            return Err(ParseError::new(
+                ErrorKind::InvalidChoice(option.key(), value, option.choices.clone()),
+                span,
+            ))
+        }
+    };
+    for validator in &option.validators {
+        resolved = validator.call(&resolved).map_err(|message| {
+            ParseError::new(
+                ErrorKind::InvalidValue(option.key(), value.clone(), message),
+                span,
+            )
+        })?;
+    }
+
+    Ok(resolved)
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argv;
+
+    fn test_spec() -> Spec {
+        Spec::new("test")
+            .option(OptionSpec::flag("--verbose").short('v'))
+            .option(OptionSpec::flag("-x"))
+            .option(OptionSpec::value("--output").short('o'))
+    }
+
+    #[test]
+    fn option_spec_switches() {
+        let o1 = OptionSpec::flag("--verbose").short('v');
+        let o2 = OptionSpec::value("-o").long("--output");
+        let o3 = OptionSpec::flag("-x");
+
+        assert_eq!(o1.key(), "--verbose");
+        assert_eq!(o2.key(), "--output");
+        assert_eq!(o3.key(), "-x");
+        assert!(o1.matches("--verbose"));
+        assert!(o1.matches("-v"));
+        assert!(!o1.matches("-verbose"));
+        assert!(!o1.matches("--v"));
+        assert!(o2.matches("--output"));
+        assert!(!o3.matches("--x"));
+        assert_eq!(o1.takes_value(), false);
+        assert_eq!(o2.takes_value(), true);
+    }
+
+    #[test]
+    fn spec_parse_values() {
+        let spec = test_spec();
+        let c1 = spec.parse(&argv(&["test", "-vo", "path", "pos"])).unwrap();
+        let c2 = spec.parse(&argv(&["test", "-xopath"])).unwrap();
+        let c3 = spec
+            .parse(&argv(&["test", "--output=a=b", "--verbose"]))
+            .unwrap();
+        let c4 = spec
+            .parse(&argv(&["test", "--output", "-x", "-o=last"]))
+            .unwrap();
+
+        assert_eq!(c1.values.get("--verbose"), Some(&Value::Flag(true)));
+        assert_eq!(
+            c1.values.get("--output"),
+            Some(&Value::Single("path".to_string()))
+        );
+        assert_eq!(c1.values.get("-x"), None);
+        assert_eq!(c2.values.get("-x"), Some(&Value::Flag(true)));
+        assert_eq!(
+            c2.values.get("--output"),
+            Some(&Value::Single("path".to_string())) // attached short option value
+        );
+        assert_eq!(
+            c3.values.get("--output"),
+            Some(&Value::Single("a=b".to_string()))
+        );
+        assert_eq!(
+            c4.values.get("--output"),
+            Some(&Value::Single("last".to_string())) // last occurrence wins
+        );
+        assert_eq!(c4.values.get("-x"), None); // `-x` is the value of the first `--output`
+    }
+
+    #[test]
+    fn spec_parse_negatable_flags() {
+        let spec = test_spec().option(OptionSpec::flag("--color").short('c').negatable());
+        let c1 = spec.parse(&argv(&["test", "--color"])).unwrap();
+        let c2 = spec
+            .parse(&argv(&["test", "--color", "--no-color"]))
+            .unwrap();
+        let c3 = spec.parse(&argv(&["test", "--no-color", "-vc"])).unwrap();
+        let c4 = spec.parse(&argv(&["test", "-v"])).unwrap();
+        let e1 = spec.parse(&argv(&["test", "--no-color=yes"]));
+        let e2 = spec.parse(&argv(&["test", "--no-verbose"]));
+
+        assert_eq!(c1.values.get("--color"), Some(&Value::Flag(true)));
+        assert_eq!(c2.values.get("--color"), Some(&Value::Flag(false)));
+        assert_eq!(c3.values.get("--color"), Some(&Value::Flag(true)));
+        assert_eq!(c4.values.get("--color"), None); // unspecified
+        assert_eq!(
+            e1.unwrap_err().kind,
+            ErrorKind::UnexpectedValue("--no-color".to_string())
+        );
+        assert_eq!(
+            e2.unwrap_err().kind,
+            ErrorKind::InvalidOption("--no-verbose".to_string())
+        );
+    }
+
+    #[test]
+    fn spec_parse_counted_flags() {
+        let spec = test_spec().option(
+            OptionSpec::count("-d")
+                .long("debug")
+                .decrement("-q")
+                .help("Debug"),
+        );
+        let c1 = spec.parse(&argv(&["test", "-ddd"])).unwrap();
+        let c2 = spec.parse(&argv(&["test", "-d", "-d", "-vd"])).unwrap();
+        let c3 = spec
+            .parse(&argv(&["test", "--debug", "-dqq", "-q"]))
+            .unwrap();
+        let c4 = spec.parse(&argv(&["test", "-v"])).unwrap();
+        let e1 = spec.parse(&argv(&["test", "--debug=2"]));
+        let e2 = spec.parse(&argv(&["test", "-q=1"]));
+
+        assert_eq!(c1.values.get("--debug"), Some(&Value::Count(3)));
+        assert_eq!(c2.values.get("--debug"), Some(&Value::Count(3)));
+        assert_eq!(c3.values.get("--debug"), Some(&Value::Count(-1)));
+        assert_eq!(c4.values.get("--debug"), None);
+        assert_eq!(
+            e1.unwrap_err().kind,
+            ErrorKind::UnexpectedValue("--debug".to_string())
+        );
+        assert_eq!(
+            e2.unwrap_err().kind,
+            ErrorKind::UnexpectedValue("-q".to_string())
+        );
+        assert!(spec.help().contains("  -d, --debug | -q      Debug\n"));
+    }
+
+    #[test]
+    fn spec_parse_optional_values() {
+        let spec = test_spec()
+            .option(OptionSpec::optional_value("--color", "always").value_name("WHEN"))
+            .option(OptionSpec::optional_value("-z", "9").value_name("LEVEL"));
+        let c1 = spec.parse(&argv(&["test", "--color", "never"])).unwrap();
+        let c2 = spec.parse(&argv(&["test", "--color=never"])).unwrap();
+        let c3 = spec.parse(&argv(&["test", "--color="])).unwrap();
+        let c4 = spec.parse(&argv(&["test", "-vz", "1"])).unwrap();
+        let c5 = spec.parse(&argv(&["test", "-vz1"])).unwrap();
+        let c6 = spec.parse(&argv(&["test", "-z=1", "-v"])).unwrap();
+        let c7 = spec.parse(&argv(&["test", "-v"])).unwrap();
+        let c8 = spec.parse(&argv(&["test", "-zo", "next"])).unwrap();
+
+        assert_eq!(
+            c1.values.get("--color"),
+            Some(&Value::Single("always".to_string()))
+        );
+        assert_eq!(c1.last_arg, Some("never".to_string())); // not consumed
+        assert_eq!(
+            c2.values.get("--color"),
+            Some(&Value::Single("never".to_string()))
+        );
+        assert_eq!(
+            c3.values.get("--color"),
+            Some(&Value::Single("".to_string()))
+        );
+        assert_eq!(c4.values.get("-z"), Some(&Value::Single("9".to_string())));
+        assert_eq!(c4.last_arg, Some("1".to_string()));
+        assert_eq!(c5.values.get("-z"), Some(&Value::Single("1".to_string())));
+        assert_eq!(c6.values.get("-z"), Some(&Value::Single("1".to_string())));
+        assert_eq!(c7.values.get("--color"), None);
+        // the rest of the argument is the optional value, and the next argument is not consumed
+        assert_eq!(c8.values.get("-z"), Some(&Value::Single("o".to_string())));
+        assert_eq!(c8.values.get("--output"), None);
+        assert_eq!(c8.tokens[2].kind, TokenKind::Positional);
+        assert_eq!(c8.last_arg, Some("next".to_string()));
+        assert!(spec.help().contains("      --color[=WHEN]"));
+        assert!(spec.help().contains("  -z[LEVEL]"));
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Format {
+        Json,
+        Table,
+    }
+
+    impl ValueEnum for Format {
+        fn choices() -> Vec<&'static str> {
+            vec!["json", "table"]
+        }
+
+        fn from_choice(choice: &str) -> Option<Self> {
+            match choice {
+                "json" => Some(Format::Json),
+                "table" => Some(Format::Table),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn spec_parse_choices() {
+        let spec = test_spec()
+            .option(
+                OptionSpec::value("--format")
+                    .value_enum::<Format>()
+                    .choice_alias("tab", "table")
+                    .help("Output format"),
+            )
+            .option(
+                OptionSpec::optional_value("--color", "auto")
+                    .choices(&["auto", "always", "never"])
+                    .ignore_case(),
+            );
+        let c1 = spec
+            .parse(&argv(&["test", "--format", "json", "--color"]))
+            .unwrap();
+        let c2 = spec
+            .parse(&argv(&["test", "--format=tab", "--color=NEVER"]))
+            .unwrap();
+        let e1 = spec.parse(&argv(&["test", "--format=JSON"]));
+        let e2 = spec.parse(&argv(&["test", "--color=sometimes"]));
+
+        assert_eq!(
+            c1.values
+                .get("--format")
+                .and_then(|x| x.to_enum::<Format>()),
+            Some(Format::Json)
+        );
+        assert_eq!(
+            c1.values.get("--color"),
+            Some(&Value::Single("auto".to_string()))
+        );
+        assert_eq!(
+            c2.values
+                .get("--format")
+                .and_then(|x| x.to_enum::<Format>()),
+            Some(Format::Table)
+        );
+        assert_eq!(
+            c2.values.get("--color"),
+            Some(&Value::Single("never".to_string()))
+        );
+        assert_eq!(
+            e1.unwrap_err().kind,
+            ErrorKind::InvalidChoice(
+                "--format".to_string(),
+                "JSON".to_string(),
+                vec!["json".to_string(), "table".to_string()]
+            )
+        );
+        assert_eq!(
+            format!("{}", e2.unwrap_err()),
+            "invalid value 'sometimes' for option '--color' (possible values: auto, always, never)"
+        );
+        assert!(spec
+            .help()
+            .contains("Output format [possible values: json, table]\n"));
+        assert!(spec
+            .help()
+            .contains("[possible values: auto, always, never]\n"));
+        assert_eq!(spec.complete_value("--format", ""), vec!["json", "table"]);
+        assert_eq!(spec.complete_value("--color", "A"), vec!["auto", "always"]);
+        assert_eq!(spec.complete_value("--format", "y"), Vec::<String>::new());
+        assert_eq!(spec.complete_value("-v", ""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn spec_parse_validators() {
+        let spec = test_spec()
+            .option(
+                OptionSpec::value("--port")
+                    .short('p')
+                    .value_type::<u16>()
+                    .validator(|x| match x.parse::<u16>() {
+                        Ok(0) => Err(String::from("must not be zero")),
+                        _ => Ok(()),
+                    }),
+            )
+            .option(
+                OptionSpec::value("--name")
+                    .parser(|x| Ok(x.trim().to_lowercase()))
+                    .validator(|x| {
+                        if x.is_empty() {
+                            Err(String::from("must not be empty"))
+                        } else {
+                            Ok(())
+                        }
+                    }),
+            );
+        let c1 = spec
+            .parse(&argv(&["test", "--port", "8080", "--name= Alpha "]))
+            .unwrap();
+        let e1 = spec.parse(&argv(&["test", "-v", "--port", "http"]));
+        let e2 = spec.parse(&argv(&["test", "-vp0"]));
+        let e3 = spec.parse(&argv(&["test", "--name=  "]));
+
+        assert_eq!(
+            c1.values.get("--port").and_then(|x| x.parse()),
+            Some(8080u16)
+        );
+        assert_eq!(
+            c1.values.get("--name"),
+            Some(&Value::Single("alpha".to_string()))
+        );
+        assert_eq!(
+            e1,
+            Err(ParseError::new(
+                ErrorKind::InvalidValue(
+                    "--port".to_string(),
+                    "http".to_string(),
+                    "invalid digit found in string".to_string()
+                ),
+                Some(Span {
+                    index: 3,
+                    start: 0,
+                    end: 4
+                })
+            ))
+        );
+        assert_eq!(
+            e2,
+            Err(ParseError::new(
+                ErrorKind::InvalidValue(
+                    "--port".to_string(),
+                    "0".to_string(),
+                    "must not be zero".to_string()
+                ),
+                Some(Span {
+                    index: 1,
+                    start: 3,
+                    end: 4
+                })
+            ))
+        );
+        assert_eq!(
+            format!("{}", e3.unwrap_err()),
+            "invalid value '  ' for option '--name': must not be empty"
+        );
+        assert_eq!(spec.options[3].validators.len(), 2);
+        assert_eq!(spec.clone(), spec);
+    }
+
+    #[test]
+    fn option_spec_split_and_join_list() {
+        let o1 = OptionSpec::value("--tags").delimiter(',').escape('\\');
+        let o2 = OptionSpec::value("--path").delimiter(':');
+        let o3 = OptionSpec::value("--name");
+
+        assert_eq!(o1.split_list("a,b,,c"), vec!["a", "b", "", "c"]);
+        assert_eq!(o1.split_list("a\\,b,c\\\\"), vec!["a,b", "c\\"]);
+        assert_eq!(o1.split_list("a\\"), vec!["a\\"]);
+        assert_eq!(o1.split_list(""), vec![""]);
+        assert_eq!(o2.split_list("a\\:b"), vec!["a\\", "b"]);
+        assert_eq!(o3.split_list("a,b"), vec!["a,b"]);
+        assert_eq!(o1.join_list(&["a,b", "c\\", "d"]), "a\\,b,c\\\\,d");
+        assert_eq!(
+            o1.split_list(&o1.join_list(&["a,b", "c\\"])),
+            vec!["a,b", "c\\"]
+        );
+        assert_eq!(o2.join_list(&["a", "b"]), "a:b");
+        assert_eq!(o3.join_list(&["a", "b"]), "ab");
+    }
+
+    #[test]
+    fn spec_parse_lists() {
+        let spec = test_spec()
+            .option(
+                OptionSpec::value("--tags")
+                    .short('t')
+                    .delimiter(',')
+                    .escape('\\')
+                    .reject_empty(),
+            )
+            .option(
+                OptionSpec::value("--format")
+                    .delimiter(',')
+                    .choices(&["json", "yaml"])
+                    .choice_alias("yml", "yaml"),
+            );
+        let c1 = spec
+            .parse(&argv(&["test", "--tags=a,b\\,c", "-vt", "d", "-te"]))
+            .unwrap();
+        let c2 = spec.parse(&argv(&["test", "--format=yml,json"])).unwrap();
+        let e1 = spec.parse(&argv(&["test", "--tags=a,,b"]));
+        let e2 = spec.parse(&argv(&["test", "--format=json,toml"]));
+
+        assert_eq!(
+            c1.values.get("--tags").and_then(|x| x.as_list()),
+            Some(
+                &[
+                    "a".to_string(),
+                    "b,c".to_string(),
+                    "d".to_string(),
+                    "e".to_string()
+                ][..]
+            )
+        );
+        assert_eq!(
+            c2.values.get("--format"),
+            Some(&Value::List(vec!["yaml".to_string(), "json".to_string()]))
+        );
+        assert_eq!(
+            e1.unwrap_err().kind,
+            ErrorKind::InvalidValue(
+                "--tags".to_string(),
+                "a,,b".to_string(),
+                "empty list items are not allowed".to_string()
+            )
+        );
+        assert_eq!(
+            e2.unwrap_err().kind,
+            ErrorKind::InvalidChoice(
+                "--format".to_string(),
+                "toml".to_string(),
+                vec!["json".to_string(), "yaml".to_string()]
+            )
+        );
+        assert!(spec.help().contains("-t, --tags <VALUE>[,...]"));
+    }
+
+    #[test]
+    fn spec_parse_maps() {
+        let spec = test_spec()
+            .option(OptionSpec::map("-D"))
+            .option(
+                OptionSpec::map("--set")
+                    .short('s')
+                    .duplicate_keys(DuplicateKeys::Error),
+            )
+            .option(OptionSpec::map("--env").duplicate_keys(DuplicateKeys::Collect));
+        let c1 = spec
+            .parse(&argv(&[
+                "test",
+                "-DNAME=VALUE",
+                "-vD",
+                "A=1",
+                "-D=B=",
+                "-DNAME=last",
+                "--set",
+                "a.b=c",
+                "-sx.y=z",
+                "--env=K=1",
+                "--env",
+                "K=2",
+            ]))
+            .unwrap();
+        let e1 = spec.parse(&argv(&["test", "--set=a=1", "-s", "a=2"]));
+        let e2 = spec.parse(&argv(&["test", "-DNAME"]));
+        let e3 = spec.parse(&argv(&["test", "--set==x"]));
+        let e4 = spec.parse(&argv(&["test", "-D"]));
+
+        let defines = c1.values.get("-D").unwrap();
+        assert_eq!(
+            defines.as_map().unwrap(),
+            &[
+                ("NAME".to_string(), "last".to_string()),
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "".to_string())
+            ]
+        );
+        assert_eq!(defines.get("NAME"), Some("last"));
+        assert_eq!(defines.get("C"), None);
+        assert_eq!(c1.values.get("--set").unwrap().get("a.b"), Some("c"));
+        assert_eq!(c1.values.get("--set").unwrap().get("x.y"), Some("z"));
+        assert_eq!(
+            c1.values.get("--env"),
+            Some(&Value::Map(vec![
+                ("K".to_string(), "1".to_string()),
+                ("K".to_string(), "2".to_string())
+            ]))
+        );
+        assert_eq!(c1.values.get("--env").unwrap().get("K"), Some("2"));
+        assert_eq!(
+            e1,
+            Err(ParseError::new(
+                ErrorKind::InvalidValue(
+                    "--set".to_string(),
+                    "a=2".to_string(),
+                    "duplicate key 'a'".to_string()
+                ),
+                Some(Span {
+                    index: 3,
+                    start: 0,
+                    end: 3
+                })
+            ))
+        );
+        assert_eq!(
+            e2.unwrap_err().kind,
+            ErrorKind::InvalidValue(
+                "-D".to_string(),
+                "NAME".to_string(),
+                "expected KEY=VALUE".to_string()
+            )
+        );
+        assert!(e3.is_err());
+        assert_eq!(
+            e4.unwrap_err().kind,
+            ErrorKind::MissingValue("-D".to_string())
+        );
+        assert!(spec.help().contains("  -D <KEY=VALUE>\n"));
+    }
+
+    #[test]
+    fn verbosity_from_count() {
+        assert_eq!(Verbosity::from_count(0, Verbosity::Warn), Verbosity::Warn);
+        assert_eq!(Verbosity::from_count(1, Verbosity::Warn), Verbosity::Info);
+        assert_eq!(Verbosity::from_count(3, Verbosity::Warn), Verbosity::Trace);
+        assert_eq!(Verbosity::from_count(4, Verbosity::Warn), Verbosity::Trace);
+        assert_eq!(Verbosity::from_count(-2, Verbosity::Warn), Verbosity::Off);
+        assert_eq!(Verbosity::from_count(-9, Verbosity::Warn), Verbosity::Off);
+        assert_eq!(
+            Verbosity::from_count(i64::MAX, Verbosity::Trace),
+            Verbosity::Trace
+        );
+        assert!(Verbosity::Debug > Verbosity::Info);
+    }
+
+    #[test]
+    fn spec_help() {
+        let spec = Spec::new("test")
+            .option(
+                OptionSpec::flag("--color")
+                    .negatable()
+                    .help("Colorize output"),
+            )
+            .option(OptionSpec::flag("--verbose").short('v').negatable())
+            .option(OptionSpec::flag("-x").help("Extract"))
+            .option(OptionSpec::value("--output").short('o').value_name("PATH"));
+
+        assert_eq!(
+            spec.help(),
+            "Usage: test [OPTIONS]\n\
+             \n\
+             Options:\n      \
+             --[no-]color     Colorize output\n  \
+             -v, --[no-]verbose\n  \
+             -x                   Extract\n  \
+             -o, --output <PATH>\n"
+        );
+        assert_eq!(spec.help().matches("color").count(), 1);
+        assert_eq!(Spec::new("test").help(), "Usage: test [OPTIONS]\n");
+    }
+
+    #[test]
+    fn spec_parse_tokens_and_fields() {
+        let spec = test_spec().order(ParseOrder::RequireOrder);
+        let c = spec
+            .parse(&argv(&["test", "-o", "path", "cmd", "-v"]))
+            .unwrap();
+
+        assert_eq!(c.tokens[2].kind, TokenKind::OptionValue);
+        assert_eq!(c.options, vec!["-o".to_string()]);
+        assert_eq!(
+            c.remainder_argv,
+            Some(vec!["cmd".to_string(), "-v".to_string()])
+        );
+        assert_eq!(c.values.get("--verbose"), None);
+    }
+
+    #[test]
+    fn spec_parse_trailing_var_arg() {
+        let spec = test_spec().trailing_var_arg("CMD");
+        let c1 = spec
+            .parse(&argv(&["test", "-o", "path", "cargo", "build", "-v"]))
+            .unwrap();
+        let c2 = spec
+            .parse(&argv(&["test", "-v", "--", "-x", "file"]))
+            .unwrap();
+        let c3 = spec.parse(&argv(&["test", "-v"])).unwrap();
+
+        assert_eq!(
+            c1.get_value("CMD"),
+            Some(&Value::List(argv(&["cargo", "build", "-v"])))
+        );
+        assert_eq!(c1.remainder_argv, Some(argv(&["cargo", "build", "-v"])));
+        assert_eq!(c1.get_value("--verbose"), None);
+        assert_eq!(c1.get_value_source("CMD"), Some(&ValueSource::Argv));
+        assert_eq!(
+            c2.get_value("CMD"),
+            Some(&Value::List(argv(&["-x", "file"])))
+        );
+        assert_eq!(c3.get_value("CMD"), None);
+        assert!(spec.help().starts_with("Usage: test [OPTIONS] [CMD]...\n"));
+
+        let child = c1.get_trailing_command().unwrap();
+        assert_eq!(child.get_program(), "cargo");
+        assert_eq!(child.get_args().collect::<Vec<_>>(), vec!["build", "-v"]);
+    }
+
+    #[test]
+    fn spec_parse_file_values() {
+        use std::env;
+
+        let dir = env::temp_dir();
+        let token = dir.join(format!("commandlines-spec-{}-token", std::process::id()));
+        let large = dir.join(format!("commandlines-spec-{}-large", std::process::id()));
+        fs::write(&token, "secret\r\n").unwrap();
+        fs::write(&large, "0123456789").unwrap();
+        let (token_path, large_path) = (token.to_str().unwrap(), large.to_str().unwrap());
+
+        let spec = Spec::new("tool")
+            .option(
+                OptionSpec::value("--token")
+                    .short('t')
+                    .file_option("--token-file")
+                    .at_file()
+                    .max_file_size(8),
+            )
+            .option(
+                OptionSpec::value("--user")
+                    .at_file()
+                    .validator(|x| match x.len() {
+                        4 => Ok(()),
+                        _ => Err(String::from("expected 4 characters")),
+                    }),
+            )
+            .option(OptionSpec::value("--name"));
+        let parse = |args: &[&str]| spec.parse(&argv(args)).map(|c| c.values["--token"].clone());
+        let secret = Ok(Value::Single("secret".to_string()));
+
+        assert_eq!(
+            parse(&["tool", &format!("--token-file={}", token_path)]),
+            secret
+        );
+        assert_eq!(parse(&["tool", "--token-file", token_path]), secret);
+        assert_eq!(
+            parse(&["tool", &format!("--token=@{}", token_path)]),
+            secret
+        );
+        assert_eq!(parse(&["tool", &format!("-t@{}", token_path)]), secret);
+        assert_eq!(parse(&["tool", "-t", &format!("@{}", token_path)]), secret);
+        assert_eq!(
+            parse(&["tool", "--token=@@literal"]),
+            Ok(Value::Single("@literal".to_string()))
+        );
+        assert_eq!(parse(&["tool", "-t@"]), Ok(Value::Single("@".to_string())));
+        assert_eq!(
+            spec.parse(&argv(&["tool", "--name=@name"])).unwrap().values["--name"],
+            Value::Single("@name".to_string())
+        );
+
+        let e1 = parse(&["tool", &format!("--token-file={}", large_path)]);
+        let e2 = parse(&["tool", "--token=@/missing/token"]);
+        let e3 = parse(&["tool", &format!("--token=@{}", dir.display())]);
+        let e4 = parse(&["tool", "--token-file"]);
+        let e5 = spec.parse(&argv(&[
+            "tool",
+            "--token=x",
+            &format!("--user=@{}", token_path),
+        ]));
+
+        assert_eq!(
+            e1.unwrap_err().kind,
+            ErrorKind::InvalidValue(
+                "--token".to_string(),
+                large_path.to_string(),
+                format!("file '{}' exceeds the size limit of 8 bytes", large_path)
+            )
+        );
+        assert!(format!("{}", e2.unwrap_err())
+            .starts_with("invalid value '@/missing/token' for option '--token': cannot read file '/missing/token': "));
+        assert!(format!("{}", e3.unwrap_err()).ends_with(": the path is a directory"));
+        assert_eq!(
+            e4.unwrap_err(),
+            ParseError::new(
+                ErrorKind::MissingValue("--token-file".to_string()),
+                Some(Span {
+                    index: 1,
+                    start: 0,
+                    end: 12
+                })
+            )
+        );
+        assert_eq!(
+            e5.unwrap_err().kind,
+            ErrorKind::InvalidValue(
+                "--user".to_string(),
+                "secret".to_string(),
+                "expected 4 characters".to_string()
+            )
+        );
+        assert!(spec
+            .help()
+            .contains("-t, --token <VALUE> | --token-file <FILE>\n"));
+
+        fs::remove_file(&token).unwrap();
+        fs::remove_file(&large).unwrap();
+    }
+
+    #[test]
+    fn spec_sensitive_options() {
+        let spec = Spec::new("tool")
+            .option(OptionSpec::value("--password").short('p').sensitive())
+            .option(
+                OptionSpec::value("--token")
+                    .validator(|x| match x.len() {
+                        8 => Ok(()),
+                        _ => Err(String::from("expected 8 characters")),
+                    })
+                    .sensitive(),
+            )
+            .option(OptionSpec::map("-H").sensitive())
+            .option(OptionSpec::value("--user").short('u'))
+            .option(OptionSpec::flag("-v"));
+
+        assert_eq!(
+            spec.redact_argv(&argv(&[
+                "tool",
+                "--password=a",
+                "--password",
+                "b",
+                "-p=c",
+                "-vpd",
+                "-vp",
+                "e",
+                "-u",
+                "f",
+                "--bogus"
+            ])),
+            argv(&[
+                "tool",
+                "--password=****",
+                "--password",
+                "****",
+                "-p=****",
+                "-vp****",
+                "-vp",
+                "****",
+                "-u",
+                "f",
+                "--bogus"
+            ])
+        );
+
+        let c = spec
+            .parse(&argv(&[
+                "tool",
+                "-p",
+                "hunter2",
+                "-HAuth=secret",
+                "--user=me",
+                "pos",
+            ]))
+            .unwrap();
+        let redacted = c.redacted();
+
+        assert_eq!(
+            c.get_value("--password"),
+            Some(&Value::Single("hunter2".to_string()))
+        );
+        assert_eq!(c.argv[2], "hunter2");
+        assert_eq!(
+            redacted.argv,
+            argv(&["tool", "-p", "****", "-H****", "--user=me", "pos"])
+        );
+        assert_eq!(redacted.tokens[3].text, "-H****");
+        assert_eq!(
+            redacted.get_value("-H"),
+            Some(&Value::Map(vec![("Auth".to_string(), "****".to_string())]))
+        );
+        assert_eq!(
+            redacted.get_value("--user"),
+            Some(&Value::Single("me".to_string()))
+        );
+        assert_eq!(
+            c.to_string(),
+            "Command: 'tool -p **** -H**** --user=me pos'"
+        );
+        assert!(!format!("{:?}", c).contains("hunter2"));
+        assert!(!format!("{:?}", c).contains("secret"));
+
+        let c = spec.parse(&argv(&["tool", "--password=hunter2"])).unwrap();
+        assert_eq!(
+            c.definitions.get("--password"),
+            Some(&"hunter2".to_string())
+        );
+        assert_eq!(
+            c.redacted().definitions.get("--password"),
+            Some(&"****".to_string())
+        );
+        assert_eq!(c.redacted().first_arg, Some("--password=****".to_string()));
+
+        let e = spec
+            .parse(&argv(&["tool", "--token", "hunter2"]))
+            .unwrap_err();
+        assert_eq!(
+            e.kind,
+            ErrorKind::InvalidValue(
+                "--token".to_string(),
+                "****".to_string(),
+                "expected 8 characters".to_string()
+            )
+        );
+        let a = argv(&["tool", "-vp", "hunter2", "--token", "hunter2"]);
+        let e = spec.parse(&a).unwrap_err();
+        assert_eq!(
+            spec.render_error(&a, &e),
+            "error: invalid value '****' for option '--token': expected 8 characters\n  tool -vp **** --token ****\n                        ^^^^"
+        );
+        let a = argv(&["tool", "--token=hunter2", "-vp", "hunter2"]);
+        let e = spec.parse(&a).unwrap_err();
+        assert_eq!(
+            spec.render_error(&a, &e),
+            "error: invalid value '****' for option '--token': expected 8 characters\n  tool --token=**** -vp ****\n               ^^^^"
+        );
+    }
+
+    #[test]
+    fn spec_parse_errors() {
+        let spec = test_spec().option(OptionSpec::value("--name").required());
+        let e1 = spec.parse(&argv(&["test", "--name=x", "-vzo", "p"]));
+        let e2 = spec.parse(&argv(&["test", "--name=x", "--bogus"]));
+        let e3 = spec.parse(&argv(&["test", "--name=x", "-o"]));
+        let e4 = spec.parse(&argv(&["test", "--name=x", "--verbose=yes"]));
+        let e5 = spec.parse(&argv(&["test", "--name=x", "-xv=yes"]));
+        let e6 = spec.parse(&argv(&["test", "-v"]));
+
+        assert_eq!(
+            e1,
+            Err(ParseError::new(
+                ErrorKind::InvalidOption("-z".to_string()),
+                Some(Span {
+                    index: 2,
+                    start: 2,
+                    end: 3
+                })
+            ))
+        );
+        assert_eq!(
+            e2,
+            Err(ParseError::new(
+                ErrorKind::InvalidOption("--bogus".to_string()),
+                Some(Span {
+                    index: 2,
+                    start: 0,
+                    end: 7
+                })
+            ))
+        );
+        assert_eq!(
+            e3,
+            Err(ParseError::new(
+                ErrorKind::MissingValue("--output".to_string()),
+                Some(Span {
+                    index: 2,
+                    start: 1,
+                    end: 2
+                })
+            ))
+        );
+        assert_eq!(
+            e4,
+            Err(ParseError::new(
+                ErrorKind::UnexpectedValue("--verbose".to_string()),
+                Some(Span {
+                    index: 2,
+                    start: 0,
+                    end: 9
+                })
+            ))
+        );
+        assert_eq!(
+            e5,
+            Err(ParseError::new(
+                ErrorKind::UnexpectedValue("--verbose".to_string()),
+                Some(Span {
+                    index: 2,
+                    start: 3,
+                    end: 7
+                })
+            ))
+        );
+        assert_eq!(
+            e6,
+            Err(ParseError::new(
+                ErrorKind::MissingRequired("--name".to_string()),
+                None
+            ))
+        );
+    }
+}
@@ -0,0 +1,474 @@
+// Copyright 2018 Christopher Simpkins
+// Licensed under the MIT license
+
+//! Interactive prompt support for missing required option values
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use errors::{ErrorKind, ParseError};
+use spec::{map_value, option_value, OptionKind, OptionSpec, Spec, Value, ValueSource};
+use Command;
+
+/// A parser that prompts for the values of required options that are missing in the command line arguments
+///
+/// # Remarks
+/// Options that take a value are prompted for in the order that they are defined in the `Spec`.  The prompt is the option help text, or the option string when there is no help text, followed by the option value choices.  The input for a sensitive option is hidden, and the prompt fails when the terminal echo cannot be disabled.  An invalid value is reported and prompted for again up to the maximum number of attempts.
+///
+/// Prompted values are recorded as `ValueSource::Prompt` in `Command.value_sources`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use commandlines::spec::{OptionSpec, Prompter, Spec};
+///
+/// let spec = Spec::new("tool")
+///     .option(OptionSpec::value("--username").required().help("Username"))
+///     .option(OptionSpec::value("--password").required().sensitive().help("Password"));
+/// let argv = vec![String::from("tool"), String::from("--username=me")];
+///
+/// let mut reader = Cursor::new("hunter2\n");
+/// let mut writer: Vec<u8> = Vec::new();
+/// let c = Prompter::new(&spec).parse_with(&argv, &mut reader, &mut writer).unwrap();
+///
+/// assert_eq!(c.get_value("--password").and_then(|v| v.as_str()), Some("hunter2"));
+/// assert_eq!(String::from_utf8(writer).unwrap(), "Password: \n");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Prompter<'a> {
+    spec: &'a Spec,
+    max_attempts: usize,
+}
+
+impl<'a> Prompter<'a> {
+    /// Instantiates and returns a new `Prompter` struct for `spec` with a maximum of three attempts for each value
+    pub fn new(spec: &'a Spec) -> Self {
+        Prompter {
+            spec,
+            max_attempts: 3,
+        }
+    }
+
+    /// Returns the `Prompter` with the maximum number of attempts `attempts` to enter a valid value for an option
+    pub fn max_attempts(mut self, attempts: usize) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Returns `Result<Command, ParseError>` with a `Command` that is parsed from the command line arguments in `argv` with missing required values that are entered at the terminal
+    ///
+    /// # Remarks
+    /// The prompts are written to the standard error stream and the values are read from the standard input stream.  Missing required values are an error, as in `Spec::parse`, when the standard input stream is not a terminal.
+    pub fn parse(&self, argv: &[String]) -> Result<Command, ParseError> {
+        let stdin = io::stdin();
+        if !stdin.is_terminal() {
+            return self.spec.parse(argv);
+        }
+        self.prompt(argv, &mut stdin.lock(), &mut io::stderr(), &mut hide_input)
+    }
+
+    /// Returns `Result<Command, ParseError>` with a `Command` that is parsed from the command line arguments in `argv` with missing required values that are read from `reader` after a prompt is written to `writer`
+    ///
+    /// # Remarks
+    /// The end of the `reader` input before a valid value is entered is reported as a missing required option error.  A line break is written after the input of a hidden value because the line break is not echoed at a terminal.
+    pub fn parse_with<R: BufRead, W: Write>(
+        &self,
+        argv: &[String],
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<Command, ParseError> {
+        self.prompt(argv, reader, writer, &mut || Some(EchoGuard::new(|| {})))
+    }
+
+    // Returns the parsed `Command` with the prompted values, and disables the input echo with `hide`
+    // for hidden values
+    fn prompt(
+        &self,
+        argv: &[String],
+        reader: &mut dyn BufRead,
+        writer: &mut dyn Write,
+        hide: &mut HideInput,
+    ) -> Result<Command, ParseError> {
+        let (tokens, mut values) = self.spec.parse_argv(argv)?;
+        let mut sources: HashMap<String, ValueSource> = values
+            .keys()
+            .map(|x| (x.clone(), ValueSource::Argv))
+            .collect();
+        for option in &self.spec.options {
+            if !option.required
+                || values.contains_key(&option.key())
+                || !(option.takes_value() || option.takes_optional_value())
+            {
+                continue;
+            }
+            let value = self.prompt_value(option, &values, reader, writer, hide)?;
+            values.insert(option.key(), value);
+            sources.insert(option.key(), ValueSource::Prompt);
+        }
+        self.spec.check_required(&values)?;
+
+        Ok(self.spec.command(argv, tokens, values, sources))
+    }
+
+    // Returns the value of `option` that is entered after the prompt, or the last invalid value
+    // error after the maximum number of attempts
+    fn prompt_value(
+        &self,
+        option: &OptionSpec,
+        values: &HashMap<String, Value>,
+        reader: &mut dyn BufRead,
+        writer: &mut dyn Write,
+        hide: &mut HideInput,
+    ) -> Result<Value, ParseError> {
+        let missing = || ParseError::new(ErrorKind::MissingRequired(option.key()), None);
+        let mut error = missing();
+        for _ in 0..self.max_attempts {
+            // the echo is restored when the guard is dropped, including on an error or a panic
+            let guard = match option.sensitive {
+                true => Some(hide().ok_or_else(|| {
+                    ParseError::new(ErrorKind::InsecureInput(option.key()), None)
+                })?),
+                false => None,
+            };
+            write!(writer, "{}: ", prompt_text(option)).map_err(|_| missing())?;
+            writer.flush().map_err(|_| missing())?;
+            let mut line = String::new();
+            let read = reader.read_line(&mut line);
+            if guard.is_some() {
+                drop(guard);
+                writeln!(writer).map_err(|_| missing())?;
+            }
+            match read {
+                Ok(0) | Err(_) => return Err(missing()),
+                Ok(_) => {}
+            }
+            let text = line.trim_end_matches(['\n', '\r']).to_string();
+            let parsed = if text.is_empty() {
+                Err(ParseError::new(
+                    ErrorKind::InvalidValue(
+                        option.key(),
+                        text,
+                        String::from("a value is required"),
+                    ),
+                    None,
+                ))
+            } else if option.kind == OptionKind::Map {
+                map_value(option, text, None, values)
+            } else {
+                option_value(option, text, None, values)
+            };
+            match parsed {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    error = self.spec.redact_error(e);
+                    writeln!(writer, "error: {}", error).map_err(|_| missing())?;
+                }
+            }
+        }
+
+        Err(error)
+    }
+}
+
+// Returns the prompt for the value of `option` (e.g., `Output format (json/table)`)
+fn prompt_text(option: &OptionSpec) -> String {
+    let label = if option.help.is_empty() {
+        option.key()
+    } else {
+        option.help.clone()
+    };
+    if option.choices.is_empty() {
+        label
+    } else {
+        format!("{} ({})", label, option.choices.join("/"))
+    }
+}
+
+// Disables the input echo and returns the guard that restores it, or `None` when the echo cannot be disabled
+type HideInput<'a> = dyn FnMut() -> Option<EchoGuard> + 'a;
+
+// Restores the input echo when it is dropped
+struct EchoGuard {
+    restore: Option<Box<dyn FnOnce()>>,
+}
+
+impl EchoGuard {
+    // Instantiates and returns a new `EchoGuard` that calls `restore` when it is dropped
+    fn new<F: FnOnce() + 'static>(restore: F) -> Self {
+        EchoGuard {
+            restore: Some(Box::new(restore)),
+        }
+    }
+}
+
+impl Drop for EchoGuard {
+    fn drop(&mut self) {
+        if let Some(restore) = self.restore.take() {
+            restore();
+        }
+    }
+}
+
+// Disables the terminal echo of input characters, or returns `None` when the echo cannot be disabled
+//
+// The echo is disabled and restored by a shell process that ignores interrupts and restores the echo
+// when its standard input stream is closed, which happens when the guard is dropped and when this
+// process is interrupted or killed.
+#[cfg(unix)]
+fn hide_input() -> Option<EchoGuard> {
+    use std::process::{self, Stdio};
+
+    let script = "trap '' INT QUIT TSTP; stty -echo < /dev/tty || exit 1; echo off; read -r _; stty echo < /dev/tty";
+    let mut child = process::Command::new("sh")
+        .args(["-c", script])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut status = String::new();
+    if let Some(stdout) = child.stdout.take() {
+        let _ = io::BufReader::new(stdout).read_line(&mut status);
+    }
+    if status.trim_end() != "off" {
+        let _ = child.kill();
+        let _ = child.wait();
+        return None;
+    }
+
+    Some(EchoGuard::new(move || {
+        drop(child.stdin.take());
+        let _ = child.wait();
+    }))
+}
+
+// Disables the terminal echo of input characters (not supported)
+#[cfg(not(unix))]
+fn hide_input() -> Option<EchoGuard> {
+    None
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argv;
+    use std::cell::RefCell;
+    use std::io::Cursor;
+    use std::panic;
+    use std::rc::Rc;
+
+    fn test_spec() -> Spec {
+        Spec::new("tool")
+            .option(OptionSpec::value("--username").required().help("Username"))
+            .option(OptionSpec::value("--pin").required().sensitive().validator(
+                |x| match x.len() {
+                    4 => Ok(()),
+                    _ => Err(String::from("expected 4 digits")),
+                },
+            ))
+            .option(
+                OptionSpec::value("--format")
+                    .choices(&["json", "table"])
+                    .required(),
+            )
+            .option(OptionSpec::value("--output"))
+    }
+
+    // Returns the input echo function that records the echo changes in `echoes`
+    fn record_echo(echoes: &Rc<RefCell<String>>) -> impl FnMut() -> Option<EchoGuard> {
+        let echoes = echoes.clone();
+        move || {
+            echoes.borrow_mut().push_str("[-echo]");
+            let restored = echoes.clone();
+            Some(EchoGuard::new(move || {
+                restored.borrow_mut().push_str("[echo]")
+            }))
+        }
+    }
+
+    // Returns the parsed command and the prompt output for the input `input`
+    fn prompt(
+        prompter: &Prompter,
+        args: &[&str],
+        input: &str,
+    ) -> (Result<Command, ParseError>, String) {
+        let mut writer: Vec<u8> = Vec::new();
+        let echoes = Rc::new(RefCell::new(String::new()));
+        let result = prompter.prompt(
+            &argv(args),
+            &mut Cursor::new(input.to_string()),
+            &mut writer,
+            &mut record_echo(&echoes),
+        );
+        let mut output = String::from_utf8(writer).unwrap();
+        output.push_str(&echoes.borrow());
+        (result, output)
+    }
+
+    // A reader that panics when it is read
+    struct PanicReader;
+
+    impl io::Read for PanicReader {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            panic!("read failure")
+        }
+    }
+
+    impl BufRead for PanicReader {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            panic!("read failure")
+        }
+
+        fn consume(&mut self, _amt: usize) {}
+    }
+
+    #[test]
+    fn prompter_prompts_for_missing_required_values() {
+        let spec = test_spec();
+        let prompter = Prompter::new(&spec);
+        let (c, output) = prompt(&prompter, &["tool", "--pin=1234"], "me\r\n\nJSON\njson\n");
+        let c = c.unwrap();
+
+        assert_eq!(
+            output,
+            "Username: \
+             --format (json/table): error: invalid value '' for option '--format': a value is required\n\
+             --format (json/table): error: invalid value 'JSON' for option '--format' (possible values: json, table)\n\
+             --format (json/table): "
+        );
+        assert_eq!(
+            c.get_value("--username"),
+            Some(&Value::Single("me".to_string()))
+        );
+        assert_eq!(
+            c.get_value("--format"),
+            Some(&Value::Single("json".to_string()))
+        );
+        assert_eq!(c.get_value_source("--username"), Some(&ValueSource::Prompt));
+        assert_eq!(c.get_value_source("--pin"), Some(&ValueSource::Argv));
+        assert_eq!(c.get_value("--output"), None);
+    }
+
+    #[test]
+    fn prompter_hides_sensitive_values() {
+        let spec = test_spec();
+        let prompter = Prompter::new(&spec);
+        let (c, output) = prompt(
+            &prompter,
+            &["tool", "--username=me", "--format=table"],
+            "12\n1234\n",
+        );
+
+        assert_eq!(
+            c.unwrap().get_value("--pin"),
+            Some(&Value::Single("1234".to_string()))
+        );
+        assert_eq!(
+            output,
+            "--pin: \n\
+             error: invalid value '****' for option '--pin': expected 4 digits\n\
+             --pin: \n\
+             [-echo][echo][-echo][echo]"
+        );
+    }
+
+    #[test]
+    fn prompter_errors() {
+        let spec = test_spec();
+        let (e1, _) = prompt(
+            &Prompter::new(&spec).max_attempts(2),
+            &["tool", "--username=me", "--format=json"],
+            "1\n2\n1234\n",
+        );
+        let (e2, output) = prompt(&Prompter::new(&spec), &["tool", "--pin=1234"], "me\n");
+        let (e3, _) = prompt(&Prompter::new(&spec), &["tool", "--bogus"], "");
+
+        assert_eq!(
+            e1.unwrap_err().kind,
+            ErrorKind::InvalidValue(
+                "--pin".to_string(),
+                "****".to_string(),
+                "expected 4 digits".to_string()
+            )
+        );
+        assert_eq!(
+            e2.unwrap_err().kind,
+            ErrorKind::MissingRequired("--format".to_string())
+        );
+        assert_eq!(output, "Username: --format (json/table): ");
+        assert_eq!(
+            e3.unwrap_err().kind,
+            ErrorKind::InvalidOption("--bogus".to_string())
+        );
+    }
+
+    #[test]
+    fn prompter_restores_echo() {
+        let spec = test_spec();
+        let prompter = Prompter::new(&spec);
+        let args = argv(&["tool", "--username=me", "--format=json"]);
+
+        // the end of the input is an error while the echo is disabled
+        let (e, output) = prompt(&prompter, &["tool", "--username=me", "--format=json"], "");
+        assert_eq!(
+            e.unwrap_err().kind,
+            ErrorKind::MissingRequired("--pin".to_string())
+        );
+        assert_eq!(output, "--pin: \n[-echo][echo]");
+
+        let echoes = Rc::new(RefCell::new(String::new()));
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            prompter.prompt(
+                &args,
+                &mut PanicReader,
+                &mut Vec::new(),
+                &mut record_echo(&echoes),
+            )
+        }));
+        assert!(result.is_err());
+        assert_eq!(*echoes.borrow(), "[-echo][echo]");
+    }
+
+    #[test]
+    fn prompter_insecure_input_error() {
+        let spec = test_spec();
+        let mut writer: Vec<u8> = Vec::new();
+        let e = Prompter::new(&spec)
+            .prompt(
+                &argv(&["tool", "--format=json"]),
+                &mut Cursor::new("me\n1234\n"),
+                &mut writer,
+                &mut || None,
+            )
+            .unwrap_err();
+
+        assert_eq!(e.kind, ErrorKind::InsecureInput("--pin".to_string()));
+        assert_eq!(
+            e.to_string(),
+            "cannot hide the input of sensitive option '--pin'"
+        );
+        assert_eq!(String::from_utf8(writer).unwrap(), "Username: ");
+    }
+
+    #[test]
+    fn prompter_parse_with() {
+        let spec = test_spec();
+        let mut writer: Vec<u8> = Vec::new();
+        let c = Prompter::new(&spec)
+            .parse_with(
+                &argv(&["tool", "--username=me", "--format=json"]),
+                &mut Cursor::new("1234\n"),
+                &mut writer,
+            )
+            .unwrap();
+
+        assert_eq!(
+            c.get_value("--pin"),
+            Some(&Value::Single("1234".to_string()))
+        );
+        assert_eq!(String::from_utf8(writer).unwrap(), "--pin: \n");
+        assert_eq!(c.to_string(), "Command: 'tool --username=me --format=json'");
+    }
+}
@@ -0,0 +1,461 @@
+// Copyright 2018 Christopher Simpkins
+// Licensed under the MIT license
+
+//! Interactive shell (REPL) command line parsing and completion support
+
+use errors::{ErrorKind, ParseError};
+use parsers::{self, Token, TokenKind};
+use spec::{AliasTable, Spec};
+use Command;
+
+/// The command line completion candidates for the word at a cursor position
+#[derive(Clone, Debug, PartialEq)]
+pub struct Completion {
+    /// The byte offset in the line of the start of the text that a candidate replaces
+    pub start: usize,
+    /// The completion candidates, quoted as command line text that replaces the text at `start`
+    pub candidates: Vec<String>,
+}
+
+/// A parser for the lines of an interactive shell that are parsed against the same `Spec`
+///
+/// # Remarks
+/// A line is split into arguments with shell quoting rules (see `parsers::split_words`) and parsed with the `Spec` name as the executable name in `argv[0]`.  Parse errors are returned for each line so that the shell can report them and continue.
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::spec::{LineParser, OptionSpec, Spec};
+///
+/// let spec = Spec::new("get")
+///     .option(OptionSpec::value("--format").choices(&["json", "table"]))
+///     .option(OptionSpec::flag("--verbose"));
+/// let parser = LineParser::new(&spec);
+///
+/// let c = parser.parse_line("--format json 'my key'").unwrap();
+/// assert_eq!(c.argv, vec!["get", "--format", "json", "my key"]);
+///
+/// let e = parser.parse_line("--format xml").unwrap_err();
+/// assert_eq!(
+///     parser.render_error("--format xml", &e),
+///     "error: invalid value 'xml' for option '--format' (possible values: json, table)\n  --format xml\n           ^^^"
+/// );
+///
+/// let completion = parser.complete("--format j", 10);
+/// assert_eq!(completion.start, 9);
+/// assert_eq!(completion.candidates, vec!["json"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LineParser<'a> {
+    spec: &'a Spec,
+    aliases: Option<&'a AliasTable>,
+}
+
+impl<'a> LineParser<'a> {
+    /// Instantiates and returns a new `LineParser` struct for `spec`
+    pub fn new(spec: &'a Spec) -> Self {
+        LineParser {
+            spec,
+            aliases: None,
+        }
+    }
+
+    /// Returns the `LineParser` with the command aliases in `aliases` that are expanded in each line
+    pub fn aliases(mut self, aliases: &'a AliasTable) -> Self {
+        self.aliases = Some(aliases);
+        self
+    }
+
+    /// Returns `Result<Vec<String>, ParseError>` with the command line arguments of `line` with the `Spec` name at index position `0`
+    ///
+    /// Returns an `ErrorKind::InvalidSyntax` error for an unterminated quote or escape.
+    pub fn argv(&self, line: &str) -> Result<Vec<String>, ParseError> {
+        let words = parsers::split_words(line)
+            .map_err(|e| ParseError::new(ErrorKind::InvalidSyntax(e), None))?;
+        let mut argv = vec![self.spec.name.clone()];
+        argv.extend(words);
+        Ok(argv)
+    }
+
+    /// Returns `Result<Command, ParseError>` with a `Command` that is parsed from the line `line` and validated against the specification
+    ///
+    /// # Remarks
+    /// The `ParseError` locates the argument in the command line arguments of the line.  Use `LineParser::render_error` to locate the error in the line.
+    pub fn parse_line(&self, line: &str) -> Result<Command, ParseError> {
+        let argv = self.argv(line)?;
+        match self.aliases {
+            Some(aliases) => aliases.parse(self.spec, &argv),
+            None => self.spec.parse(&argv),
+        }
+    }
+
+    /// Returns `String` with the `error` message, the line `line`, and a caret underline of the location of the error in the line
+    ///
+    /// # Remarks
+    /// A quoted or escaped argument is underlined as a whole.  The values of sensitive options are not redacted in the line.
+    pub fn render_error(&self, line: &str, error: &ParseError) -> String {
+        let mut rendered = format!("error: {}\n  {}", error, line);
+        let words = parsers::split_words_partial(line);
+        let span = match error.span {
+            Some(x) if x.index > 0 => x,
+            _ => return rendered,
+        };
+        let word = match words.get(span.index - 1) {
+            Some(x) => x,
+            None => return rendered,
+        };
+        let raw = &line[word.start..word.end];
+        let (start, end) = if raw == word.text && span.start <= span.end && span.end <= raw.len() {
+            (word.start + span.start, word.start + span.end)
+        } else {
+            (word.start, word.end)
+        };
+        let offset = 2 + line[..start].chars().count();
+        let width = line[start..end].chars().count().max(1);
+        rendered.push('\n');
+        rendered.push_str(&" ".repeat(offset));
+        rendered.push_str(&"^".repeat(width));
+
+        rendered
+    }
+
+    /// Returns `Completion` with the command line completion candidates for the word of `line` at the byte offset `cursor`
+    ///
+    /// # Remarks
+    /// The candidates are the option strings that begin with an option prefix (e.g., `--f`), and the option value choices that begin with the prefix of the value of an option (e.g., `--format j` or `--format=j`).  Option strings are not completed after the end of option parsing.  Use `Completion.start` to replace the word before the cursor with a candidate in a line editor.
+    ///
+    /// The candidates are quoted with the quotes of the word before the cursor (e.g., `'a b'` for the word `'a`), and the special characters of a candidate in an unquoted word are escaped with a backslash (e.g., `a\ b` for the word `a`).
+    pub fn complete(&self, line: &str, cursor: usize) -> Completion {
+        let mut cursor = cursor.min(line.len());
+        while !line.is_char_boundary(cursor) {
+            cursor -= 1;
+        }
+        let mut words = parsers::split_words_partial(&line[..cursor]);
+        // the word before the cursor is the completion prefix unless the cursor follows whitespace
+        let word = match words.last() {
+            Some(x) if x.end == cursor => words.pop(),
+            _ => None,
+        };
+        let (start, prefix) = match word {
+            Some(x) => (x.start, x.text),
+            None => (cursor, String::new()),
+        };
+        let mut argv = vec![self.spec.name.clone()];
+        argv.extend(words.into_iter().map(|x| x.text));
+        argv.push(prefix);
+        let raw = &line[start..cursor];
+
+        Completion {
+            start,
+            candidates: self
+                .candidates(&argv)
+                .iter()
+                .map(|x| quote_candidate(raw, x))
+                .collect(),
+        }
+    }
+
+    // Returns the completion candidates for the last argument in `argv`
+    fn candidates(&self, argv: &[String]) -> Vec<String> {
+        let tokens = self.spec.tokenize(argv);
+        let (token, previous) = match tokens.split_last() {
+            Some((x, rest)) => (x, rest.last()),
+            None => return Vec::new(),
+        };
+        match token.kind {
+            TokenKind::OptionValue => match previous.and_then(|x| self.value_switch(x)) {
+                Some(switch) => self.spec.complete_value(&switch, &token.text),
+                None => Vec::new(),
+            },
+            TokenKind::LongWithValue => {
+                let (switch, value) = token.text.split_at(token.text.find('=').unwrap_or(0));
+                self.spec
+                    .complete_value(switch, &value[1..])
+                    .into_iter()
+                    .map(|x| format!("{}={}", switch, x))
+                    .collect()
+            }
+            TokenKind::ShortFlag
+            | TokenKind::ShortCluster
+            | TokenKind::LongFlag
+            | TokenKind::Stdin => self.switches(&token.text),
+            _ => Vec::new(),
+        }
+    }
+
+    // Returns the option string of the option token `token` that takes the argument that follows
+    // it as its value, or `None` if the value is a file path
+    fn value_switch(&self, token: &Token) -> Option<String> {
+        let switch = match token.kind {
+            TokenKind::LongFlag => token.text.clone(),
+            _ => token.short_options().last()?.0.clone(),
+        };
+        match self.spec.find(&switch) {
+            Some(option) if !option.reads_file(&switch) => Some(switch),
+            _ => None,
+        }
+    }
+
+    // Returns the option strings of the `Spec` options that begin with `prefix`
+    fn switches(&self, prefix: &str) -> Vec<String> {
+        let mut switches: Vec<String> = Vec::new();
+        for option in &self.spec.options {
+            if let Some(long) = &option.long {
+                switches.push(format!("--{}", long));
+                if option.negatable {
+                    switches.push(format!("--no-{}", long));
+                }
+            }
+            if let Some(short) = option.short {
+                switches.push(format!("-{}", short));
+            }
+            switches.extend(option.decrement.iter().cloned());
+            switches.extend(option.file_option.iter().cloned());
+        }
+        switches.retain(|x| x.starts_with(prefix));
+        switches
+    }
+}
+
+// Returns the completion `candidate` quoted with the quotes of the command line text `word` that it replaces
+fn quote_candidate(word: &str, candidate: &str) -> String {
+    // the text before the first quote of the word (e.g., `--format=` in `--format='t`) is not quoted,
+    // and a word with an escape character before the first quote is escaped
+    let i = match word.find(['\'', '"', '\\']) {
+        Some(i) if !word[i..].starts_with('\\') => i,
+        _ => return escape_text(candidate),
+    };
+    let quote = word.as_bytes()[i] as char;
+    match candidate.strip_prefix(&word[..i]) {
+        Some(rest) => format!("{}{}", &word[..i], quote_text(rest, quote)),
+        None => quote_text(candidate, quote),
+    }
+}
+
+// Returns `text` in single quotes or in double quotes with the `quote` character
+fn quote_text(text: &str, quote: char) -> String {
+    if quote == '\'' {
+        return format!("'{}'", text.replace('\'', "'\\''"));
+    }
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        if matches!(c, '"' | '\\' | '$' | '`') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Returns `text` with the whitespace and the shell special characters escaped with a backslash
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if c.is_whitespace() || "'\"\\$`&|;<>()*?[]{}#~!".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parsers::{ParseOrder, Span};
+    use spec::OptionSpec;
+
+    fn test_spec() -> Spec {
+        Spec::new("get")
+            .option(
+                OptionSpec::value("--format")
+                    .short('f')
+                    .choices(&["json", "table", "text"]),
+            )
+            .option(OptionSpec::flag("--color").negatable())
+            .option(OptionSpec::count("-v").long("verbose").decrement("-q"))
+            .option(OptionSpec::value("--token").file_option("--token-file"))
+    }
+
+    #[test]
+    fn line_parser_parse_line() {
+        let spec = test_spec();
+        let parser = LineParser::new(&spec);
+        let c1 = parser.parse_line("-f json \"a b\" c\\ d").unwrap();
+        let c2 = parser.parse_line("").unwrap();
+
+        assert_eq!(c1.argv, vec!["get", "-f", "json", "a b", "c d"]);
+        assert_eq!(
+            c1.get_value("--format").and_then(|v| v.as_str()),
+            Some("json")
+        );
+        assert_eq!(c2.argv, vec!["get"]);
+        assert_eq!(
+            parser.parse_line("-f 'json"),
+            Err(ParseError::new(
+                ErrorKind::InvalidSyntax("unterminated single quote".to_string()),
+                None
+            ))
+        );
+        assert_eq!(
+            parser.parse_line("x --bogus"),
+            Err(ParseError::new(
+                ErrorKind::InvalidOption("--bogus".to_string()),
+                Some(Span {
+                    index: 2,
+                    start: 0,
+                    end: 7
+                })
+            ))
+        );
+        // the parser is reusable after an error
+        assert!(parser.parse_line("--color").is_ok());
+    }
+
+    #[test]
+    fn line_parser_parse_line_aliases() {
+        let spec = test_spec();
+        let aliases = AliasTable::new().alias("j", "--format json");
+        let parser = LineParser::new(&spec).aliases(&aliases);
+        let c = parser.parse_line("j x").unwrap();
+
+        assert_eq!(c.argv, vec!["get", "--format", "json", "x"]);
+        assert_eq!(
+            c.original_argv,
+            Some(vec!["get".to_string(), "j".to_string(), "x".to_string()])
+        );
+    }
+
+    #[test]
+    fn line_parser_render_error() {
+        let spec = test_spec();
+        let parser = LineParser::new(&spec);
+        let e1 = parser.parse_line("x  -f xml").unwrap_err();
+        let e2 = parser.parse_line("x '-f' \"x\"").unwrap_err();
+        let e3 = parser.parse_line("-Cv").unwrap_err();
+        let e4 = parser.parse_line("'x").unwrap_err();
+
+        assert_eq!(
+            parser.render_error("x  -f xml", &e1),
+            "error: invalid value 'xml' for option '--format' (possible values: json, table, text)\n  x  -f xml\n        ^^^"
+        );
+        assert_eq!(
+            parser.render_error("x '-f' \"x\"", &e2),
+            "error: invalid value 'x' for option '--format' (possible values: json, table, text)\n  x '-f' \"x\"\n         ^^^"
+        );
+        assert_eq!(
+            parser.render_error("-Cv", &e3),
+            "error: invalid option '-C'\n  -Cv\n   ^"
+        );
+        assert_eq!(
+            parser.render_error("'x", &e4),
+            "error: invalid command line: unterminated single quote\n  'x"
+        );
+    }
+
+    #[test]
+    fn line_parser_complete_options() {
+        let spec = test_spec();
+        let parser = LineParser::new(&spec);
+        let candidates = |line: &str| parser.complete(line, line.len()).candidates;
+
+        assert_eq!(
+            candidates("x -"),
+            vec![
+                "--format",
+                "-f",
+                "--color",
+                "--no-color",
+                "--verbose",
+                "-v",
+                "-q",
+                "--token",
+                "--token-file"
+            ]
+        );
+        assert_eq!(candidates("x --no"), vec!["--no-color"]);
+        assert_eq!(candidates("x --to"), vec!["--token", "--token-file"]);
+        assert_eq!(candidates("x -- -"), Vec::<String>::new());
+        assert_eq!(candidates("x "), Vec::<String>::new());
+        assert_eq!(
+            parser.complete("x --co --format", 6),
+            Completion {
+                start: 2,
+                candidates: vec!["--color".to_string()]
+            }
+        );
+
+        let spec = test_spec().order(ParseOrder::RequireOrder);
+        let parser = LineParser::new(&spec);
+        assert_eq!(parser.complete("x --c", 5).candidates, Vec::<String>::new());
+        assert_eq!(parser.complete("--c", 3).candidates, vec!["--color"]);
+    }
+
+    #[test]
+    fn line_parser_complete_quoted_values() {
+        let spec = Spec::new("get").option(
+            OptionSpec::value("--name")
+                .short('n')
+                .choices(&["a b", "it's", "$x\"y"]),
+        );
+        let parser = LineParser::new(&spec);
+        let candidates = |line: &str| parser.complete(line, line.len()).candidates;
+
+        assert_eq!(candidates("--name "), vec!["a\\ b", "it\\'s", "\\$x\\\"y"]);
+        assert_eq!(candidates("--name 'a"), vec!["'a b'"]);
+        assert_eq!(candidates("--name 'i"), vec!["'it'\\''s'"]);
+        assert_eq!(candidates("-n \"$"), vec!["\"\\$x\\\"y\""]);
+        assert_eq!(candidates("--name=a"), vec!["--name=a\\ b"]);
+        assert_eq!(candidates("--name=\"a"), vec!["--name=\"a b\""]);
+
+        // a completed candidate is split into the choice
+        for (line, choice) in [
+            ("--name a", "a b"),
+            ("--name 'i", "it's"),
+            ("--name \"$", "$x\"y"),
+        ] {
+            let completion = parser.complete(line, line.len());
+            let completed = format!("{}{}", &line[..completion.start], completion.candidates[0]);
+            assert_eq!(
+                parser
+                    .parse_line(&completed)
+                    .unwrap()
+                    .get_value("--name")
+                    .and_then(|v| v.as_str()),
+                Some(choice)
+            );
+        }
+    }
+
+    #[test]
+    fn line_parser_complete_values() {
+        let spec = test_spec();
+        let parser = LineParser::new(&spec);
+        let candidates = |line: &str| parser.complete(line, line.len()).candidates;
+
+        assert_eq!(candidates("--format "), vec!["json", "table", "text"]);
+        assert_eq!(candidates("--format t"), vec!["table", "text"]);
+        assert_eq!(candidates("-vf 'ta"), vec!["'table'"]);
+        assert_eq!(candidates("-vf \"ta"), vec!["\"table\""]);
+        assert_eq!(candidates("x --format='te"), vec!["--format='text'"]);
+        assert_eq!(candidates("x --format=te"), vec!["--format=text"]);
+        assert_eq!(candidates("--token-file "), Vec::<String>::new());
+        assert_eq!(candidates("--format json "), Vec::<String>::new());
+        assert_eq!(
+            parser.complete("--format 'ta", 12),
+            Completion {
+                start: 9,
+                candidates: vec!["'table'".to_string()]
+            }
+        );
+        assert_eq!(
+            parser.complete("--format", 100),
+            Completion {
+                start: 0,
+                candidates: vec!["--format".to_string()]
+            }
+        );
+    }
+}
@@ -1 +1,4 @@
+pub mod multicall;
 pub mod path;
+pub mod process;
+pub mod stdio;
@@ -0,0 +1,269 @@
+// Copyright 2018 Christopher Simpkins
+// Licensed under the MIT license
+
+//! Multicall (busybox-style) executable support
+
+use errors::{ErrorKind, ParseError};
+use parsers::{self, Span, TokenKind};
+use spec::Spec;
+use utilities::process::executable_name;
+
+/// An applet of a multicall executable with its command line arguments
+#[derive(Clone, Debug, PartialEq)]
+pub struct Applet {
+    /// The applet name
+    pub name: String,
+    /// The ordered command line arguments of the applet with the applet name at index position `0`
+    pub argv: Vec<String>,
+}
+
+/// A dispatcher for a single executable that is installed under several names (e.g., with hard links) and selects an applet with the executable name in `argv[0]`
+///
+/// # Remarks
+/// The executable name is the `argv[0]` path without the directories and an executable file extension (e.g., `ls` for `/usr/bin/ls` or `C:\bin\ls.exe`, see `process::executable_name`).  When the executable name is the main name of the executable, the first positional argument is the applet name (e.g., `busybox ls -l`) and the arguments that precede it are not included in the applet arguments.  Define the options of the main executable with `Multicall::main_spec` when an option takes a separate value (e.g., `busybox --config cfg ls`), otherwise the value is the applet name.
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::utilities::multicall::Multicall;
+///
+/// let multicall = Multicall::new("busybox").applets(&["ls", "cat"]);
+/// let argv1: Vec<String> = vec!["/bin/ls", "-l"].into_iter().map(String::from).collect();
+/// let argv2: Vec<String> = vec!["busybox", "cat", "a.txt"].into_iter().map(String::from).collect();
+///
+/// let applet1 = multicall.dispatch(&argv1).unwrap();
+/// let applet2 = multicall.dispatch(&argv2).unwrap();
+///
+/// assert_eq!(applet1.name, "ls");
+/// assert_eq!(applet1.argv, vec!["/bin/ls", "-l"]);
+/// assert_eq!(applet2.name, "cat");
+/// assert_eq!(applet2.argv, vec!["cat", "a.txt"]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Multicall {
+    main: String,
+    applets: Vec<String>,
+    main_spec: Option<Spec>,
+}
+
+impl Multicall {
+    /// Instantiates and returns a new `Multicall` struct for the main executable name `main` (e.g., `busybox`)
+    pub fn new(main: &str) -> Self {
+        Multicall {
+            main: main.to_string(),
+            applets: Vec::new(),
+            main_spec: None,
+        }
+    }
+
+    /// Returns the `Multicall` with the applet `name`
+    pub fn applet(mut self, name: &str) -> Self {
+        self.applets.push(name.to_string());
+        self
+    }
+
+    /// Returns the `Multicall` with the applets in `names`
+    pub fn applets(mut self, names: &[&str]) -> Self {
+        self.applets.extend(names.iter().map(|x| x.to_string()));
+        self
+    }
+
+    /// Returns the `Multicall` with the option definitions `spec` of the main executable that are used to find the applet name in the arguments of the main executable
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commandlines::spec::{OptionSpec, Spec};
+    /// use commandlines::utilities::multicall::Multicall;
+    ///
+    /// let multicall = Multicall::new("busybox")
+    ///     .applets(&["ls", "cat"])
+    ///     .main_spec(Spec::new("busybox").option(OptionSpec::value("--config")));
+    /// let argv: Vec<String> = vec!["busybox", "--config", "cfg", "ls"].into_iter().map(String::from).collect();
+    ///
+    /// assert_eq!(multicall.dispatch(&argv).unwrap().argv, vec!["ls"]);
+    /// ```
+    pub fn main_spec(mut self, spec: Spec) -> Self {
+        self.main_spec = Some(spec);
+        self
+    }
+
+    /// Returns `Option<&str>` with the applet for the executable path or name `executable`.
+    /// Returns `None` if the executable name is not an applet.
+    pub fn find(&self, executable: &str) -> Option<&str> {
+        let name = executable_name(executable);
+        self.applets.iter().find(|x| *x == name).map(|x| &x[..])
+    }
+
+    /// Returns `Result<Applet, ParseError>` with the applet that is selected with the executable name in `argv[0]`, or with the first positional argument when the executable name is the main name
+    ///
+    /// Returns an `ErrorKind::InvalidApplet` error if the executable name or the first positional argument is not an applet, and an `ErrorKind::MissingApplet` error if the main executable is used without a positional argument.
+    pub fn dispatch(&self, argv: &[String]) -> Result<Applet, ParseError> {
+        let executable = match argv.first() {
+            Some(x) => x,
+            None => return Err(ParseError::new(ErrorKind::MissingApplet, None)),
+        };
+        if let Some(name) = self.find(executable) {
+            return Ok(Applet {
+                name: name.to_string(),
+                argv: argv.to_vec(),
+            });
+        }
+        let span = Span {
+            index: 0,
+            start: 0,
+            end: executable.len(),
+        };
+        if executable_name(executable) != self.main {
+            return Err(ParseError::new(
+                ErrorKind::InvalidApplet(executable.to_string()),
+                Some(span),
+            ));
+        }
+
+        let tokens = match &self.main_spec {
+            Some(spec) => spec.tokenize(argv),
+            None => parsers::tokenize(argv),
+        };
+        let token = tokens
+            .into_iter()
+            .take_while(|x| x.kind != TokenKind::Terminator)
+            .find(|x| x.kind == TokenKind::Positional);
+        let token = match token {
+            Some(x) => x,
+            None => return Err(ParseError::new(ErrorKind::MissingApplet, None)),
+        };
+        match self.applets.iter().find(|x| **x == token.text) {
+            Some(name) => Ok(Applet {
+                name: name.clone(),
+                argv: argv[token.index..].to_vec(),
+            }),
+            None => Err(ParseError::new(
+                ErrorKind::InvalidApplet(token.text.clone()),
+                Some(Span {
+                    index: token.index,
+                    start: 0,
+                    end: token.text.len(),
+                }),
+            )),
+        }
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argv;
+    use spec::OptionSpec;
+
+    fn test_multicall() -> Multicall {
+        Multicall::new("box")
+            .applet("ls")
+            .applets(&["cat", "python3.11"])
+    }
+
+    #[test]
+    fn multicall_find() {
+        let multicall = test_multicall();
+
+        assert_eq!(multicall.find("ls"), Some("ls"));
+        assert_eq!(multicall.find("/usr/local/bin/ls"), Some("ls"));
+        assert_eq!(multicall.find("C:\\bin\\cat.exe"), Some("cat"));
+        assert_eq!(multicall.find("./python3.11"), Some("python3.11"));
+        assert_eq!(multicall.find("python3.11.EXE"), Some("python3.11"));
+        assert_eq!(multicall.find("cat.sh"), None);
+        assert_eq!(multicall.find("bin/ls.d/cat"), Some("cat"));
+        assert_eq!(multicall.find(".ls"), None);
+        assert_eq!(multicall.find("box"), None);
+        assert_eq!(multicall.find(""), None);
+    }
+
+    #[test]
+    fn multicall_dispatch() {
+        let multicall = test_multicall();
+
+        assert_eq!(
+            multicall.dispatch(&argv(&["/bin/ls", "-l", "cat"])),
+            Ok(Applet {
+                name: "ls".to_string(),
+                argv: argv(&["/bin/ls", "-l", "cat"]),
+            })
+        );
+        assert_eq!(
+            multicall.dispatch(&argv(&["/opt/box.exe", "-v", "cat", "-n", "a.txt"])),
+            Ok(Applet {
+                name: "cat".to_string(),
+                argv: argv(&["cat", "-n", "a.txt"]),
+            })
+        );
+    }
+
+    #[test]
+    fn multicall_dispatch_errors() {
+        let multicall = test_multicall();
+
+        assert_eq!(
+            multicall.dispatch(&argv(&["/bin/other", "ls"])),
+            Err(ParseError::new(
+                ErrorKind::InvalidApplet("/bin/other".to_string()),
+                Some(Span {
+                    index: 0,
+                    start: 0,
+                    end: 10
+                })
+            ))
+        );
+        assert_eq!(
+            multicall.dispatch(&argv(&["box", "-v", "rm", "ls"])),
+            Err(ParseError::new(
+                ErrorKind::InvalidApplet("rm".to_string()),
+                Some(Span {
+                    index: 2,
+                    start: 0,
+                    end: 2
+                })
+            ))
+        );
+        assert_eq!(
+            multicall.dispatch(&argv(&["box", "-v", "--", "ls"])),
+            Err(ParseError::new(ErrorKind::MissingApplet, None))
+        );
+        assert_eq!(
+            multicall.dispatch(&[]),
+            Err(ParseError::new(ErrorKind::MissingApplet, None))
+        );
+        assert_eq!(
+            format!("{}", multicall.dispatch(&argv(&["box"])).unwrap_err()),
+            "missing applet name"
+        );
+    }
+
+    #[test]
+    fn multicall_dispatch_main_spec() {
+        let args = argv(&["box", "--config", "cfg", "-qC", "dir", "ls", "-l"]);
+        let spec = Spec::new("box")
+            .option(OptionSpec::value("--config"))
+            .option(OptionSpec::flag("-q"))
+            .option(OptionSpec::value("-C"));
+
+        assert_eq!(
+            test_multicall().dispatch(&args),
+            Err(ParseError::new(
+                ErrorKind::InvalidApplet("cfg".to_string()),
+                Some(Span {
+                    index: 2,
+                    start: 0,
+                    end: 3
+                })
+            ))
+        );
+        assert_eq!(
+            test_multicall().main_spec(spec).dispatch(&args),
+            Ok(Applet {
+                name: "ls".to_string(),
+                argv: argv(&["ls", "-l"]),
+            })
+        );
+    }
+}
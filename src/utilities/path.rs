@@ -3,8 +3,12 @@
 
 //! Command line string operating system path utilities
 
+use std::env;
 use std::ffi::OsStr;
-use std::path::{Path, PathBuf};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::process;
 
 /// Returns an immutable `Path` for the String or string slice reference `pathstring`.
 pub fn make_path_from<S: AsRef<OsStr>>(pathstring: &S) -> &Path {
@@ -16,6 +20,215 @@ pub fn make_mut_path_from<S: AsRef<OsStr>>(pathstring: &S) -> PathBuf {
     PathBuf::from(pathstring)
 }
 
+/// Returns a `PathBuf` with a leading `~` component in `path` replaced with the home directory in the `HOME` environment variable (e.g., `~/notes.txt`).
+/// Returns `path` unchanged if it does not begin with a `~` component or the `HOME` environment variable is not defined.
+pub fn expand_tilde<P: AsRef<Path>>(path: P) -> PathBuf {
+    match env::var_os("HOME") {
+        Some(home) => expand_tilde_with(path, Path::new(&home)),
+        None => path.as_ref().to_path_buf(),
+    }
+}
+
+/// Returns a `PathBuf` with a leading `~` component in `path` replaced with the home directory `home`.
+/// Returns `path` unchanged if it does not begin with a `~` component.  The `~user` syntax is not expanded.
+pub fn expand_tilde_with<P: AsRef<Path>>(path: P, home: &Path) -> PathBuf {
+    let path = path.as_ref();
+    match path.strip_prefix("~") {
+        Ok(rest) if rest.as_os_str().is_empty() => home.to_path_buf(),
+        Ok(rest) => home.join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// Returns a `PathBuf` with `path` joined to the working directory `cwd` when it is a relative path and the `.` and `..` components lexically resolved.
+///
+/// # Remarks
+/// The file system is not accessed and symbolic links are not resolved.  A `..` component that follows the root directory is removed.
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::utilities::path::normalize_path;
+/// use std::path::{Path, PathBuf};
+///
+/// assert_eq!(normalize_path("../b/./c.txt", Path::new("/tmp/a")), PathBuf::from("/tmp/b/c.txt"));
+/// assert_eq!(normalize_path("/etc/../usr", Path::new("/tmp")), PathBuf::from("/usr"));
+/// ```
+pub fn normalize_path<P: AsRef<Path>>(path: P, cwd: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in cwd.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => normalized.push(".."),
+            },
+            _ => normalized.push(component.as_os_str()),
+        }
+    }
+    normalized
+}
+
+/// A file system check for a path value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathCheck {
+    /// The path must exist
+    MustExist,
+    /// The path must be an existing file
+    MustBeFile,
+    /// The path must be an existing directory
+    MustBeDir,
+    /// The path must not exist
+    MustNotExist,
+    /// The parent directory of the path must exist
+    ParentMustExist,
+    /// The path must be an existing file or directory that can be read
+    Readable,
+    /// The path must be an existing file or directory that can be written.  A directory can be written when a file can be created in it, which is tested with a temporary file that is removed.
+    Writable,
+}
+
+impl PathCheck {
+    /// Returns `Result<(), String>` with a failure message if `path` does not pass the check
+    pub fn check(self, path: &Path) -> Result<(), String> {
+        let display = path.display();
+        let passed = match self {
+            PathCheck::MustExist => path.exists(),
+            PathCheck::MustBeFile => path.is_file(),
+            PathCheck::MustBeDir => path.is_dir(),
+            PathCheck::MustNotExist => !path.exists(),
+            PathCheck::ParentMustExist => match path.parent() {
+                Some(parent) if parent.as_os_str().is_empty() => true,
+                Some(parent) => parent.is_dir(),
+                None => true,
+            },
+            PathCheck::Readable if path.is_dir() => fs::read_dir(path).is_ok(),
+            PathCheck::Readable => fs::File::open(path).is_ok(),
+            PathCheck::Writable if path.is_dir() => dir_is_writable(path),
+            PathCheck::Writable => fs::OpenOptions::new().write(true).open(path).is_ok(),
+        };
+        if passed {
+            return Ok(());
+        }
+        Err(match self {
+            PathCheck::MustExist => format!("'{}' does not exist", display),
+            PathCheck::MustBeFile => format!("'{}' is not a file", display),
+            PathCheck::MustBeDir => format!("'{}' is not a directory", display),
+            PathCheck::MustNotExist => format!("'{}' already exists", display),
+            PathCheck::ParentMustExist => {
+                format!("the parent directory of '{}' does not exist", display)
+            }
+            PathCheck::Readable => format!("'{}' is not readable", display),
+            PathCheck::Writable => format!("'{}' is not writable", display),
+        })
+    }
+}
+
+// Returns boolean for the question "Can a file be created in the directory `dir`?"
+//
+// The permission bits are not sufficient because they do not define who can write (e.g., a
+// directory with mode 0755 that is owned by another user)
+fn dir_is_writable(dir: &Path) -> bool {
+    for i in 0..100 {
+        let probe = dir.join(format!(".commandlines-write-{}-{}", process::id(), i));
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&probe)
+        {
+            Ok(_) => return fs::remove_file(&probe).is_ok(),
+            Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(_) => return false,
+        }
+    }
+    false
+}
+
+/// A path option value or positional argument resolver with `~` expansion, normalization relative to a working directory, and file system checks
+///
+/// # Remarks
+/// Use `spec::OptionSpec::path_value` to resolve and check an option value during parsing.  The working directory is the current working directory unless it is defined with `PathValue::cwd`.
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::utilities::path::{PathCheck, PathValue};
+/// use std::path::{Path, PathBuf};
+///
+/// let resolver = PathValue::new()
+///     .expand_tilde()
+///     .home(Path::new("/home/user"))
+///     .cwd(Path::new("/srv"))
+///     .check(PathCheck::MustNotExist);
+///
+/// assert_eq!(resolver.resolve("~/out/../out.txt"), Ok(PathBuf::from("/home/user/out.txt")));
+/// assert_eq!(resolver.resolve("data"), Ok(PathBuf::from("/srv/data")));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PathValue {
+    cwd: Option<PathBuf>,
+    home: Option<PathBuf>,
+    expand_tilde: bool,
+    checks: Vec<PathCheck>,
+}
+
+impl PathValue {
+    /// Instantiates and returns a new `PathValue` struct
+    pub fn new() -> Self {
+        PathValue::default()
+    }
+
+    /// Returns the `PathValue` with the working directory `cwd` that relative paths are resolved against
+    pub fn cwd(mut self, cwd: &Path) -> Self {
+        self.cwd = Some(cwd.to_path_buf());
+        self
+    }
+
+    /// Returns the `PathValue` defined to expand a leading `~` component to the home directory
+    pub fn expand_tilde(mut self) -> Self {
+        self.expand_tilde = true;
+        self
+    }
+
+    /// Returns the `PathValue` with the home directory `home` in place of the `HOME` environment variable for `~` expansion
+    pub fn home(mut self, home: &Path) -> Self {
+        self.home = Some(home.to_path_buf());
+        self
+    }
+
+    /// Returns the `PathValue` with the file system check `check`
+    pub fn check(mut self, check: PathCheck) -> Self {
+        self.checks.push(check);
+        self
+    }
+
+    /// Returns `Result<PathBuf, String>` with the expanded and normalized path for `value`, or a failure message if a check does not pass.
+    pub fn resolve(&self, value: &str) -> Result<PathBuf, String> {
+        if value.is_empty() {
+            return Err(String::from("empty path"));
+        }
+        let path = match (self.expand_tilde, &self.home) {
+            (true, Some(home)) => expand_tilde_with(value, home),
+            (true, None) => expand_tilde(value),
+            (false, _) => PathBuf::from(value),
+        };
+        let path = match &self.cwd {
+            Some(cwd) => normalize_path(path, cwd),
+            None => match env::current_dir() {
+                Ok(cwd) => normalize_path(path, &cwd),
+                Err(_) => normalize_path(path, Path::new("")),
+            },
+        };
+        for check in &self.checks {
+            check.check(&path)?;
+        }
+        Ok(path)
+    }
+}
+
 // Tests
 #[cfg(test)]
 mod tests {
@@ -75,4 +288,134 @@ mod tests {
         assert_eq!(pb6, PathBuf::from("bar.txt"));
     }
 
+    // Returns a new empty temporary directory for the test `name`
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            env::temp_dir().join(format!("commandlines-path-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn path_expand_tilde() {
+        let home = Path::new("/home/user");
+        assert_eq!(expand_tilde_with("~", home), PathBuf::from("/home/user"));
+        assert_eq!(
+            expand_tilde_with("~/a/b.txt", home),
+            PathBuf::from("/home/user/a/b.txt")
+        );
+        assert_eq!(expand_tilde_with("~user/a", home), PathBuf::from("~user/a"));
+        assert_eq!(expand_tilde_with("a/~/b", home), PathBuf::from("a/~/b"));
+        assert_eq!(expand_tilde_with("/abs", home), PathBuf::from("/abs"));
+    }
+
+    #[test]
+    fn path_normalize_path() {
+        let cwd = Path::new("/tmp/a");
+        assert_eq!(normalize_path("b", cwd), PathBuf::from("/tmp/a/b"));
+        assert_eq!(normalize_path("./b/../c", cwd), PathBuf::from("/tmp/a/c"));
+        assert_eq!(normalize_path("../../..", cwd), PathBuf::from("/"));
+        assert_eq!(normalize_path("/x/./y/", cwd), PathBuf::from("/x/y"));
+        assert_eq!(normalize_path("../b", Path::new("")), PathBuf::from("../b"));
+        assert_eq!(
+            normalize_path("../../b", Path::new("a")),
+            PathBuf::from("../b")
+        );
+    }
+
+    #[test]
+    fn path_checks() {
+        let dir = temp_dir("checks");
+        let file = dir.join("file.txt");
+        fs::write(&file, "text").unwrap();
+        let missing = dir.join("missing.txt");
+
+        assert_eq!(PathCheck::MustExist.check(&file), Ok(()));
+        assert_eq!(PathCheck::MustBeFile.check(&file), Ok(()));
+        assert_eq!(PathCheck::MustBeDir.check(&dir), Ok(()));
+        assert_eq!(PathCheck::MustNotExist.check(&missing), Ok(()));
+        assert_eq!(PathCheck::ParentMustExist.check(&missing), Ok(()));
+        assert_eq!(PathCheck::ParentMustExist.check(Path::new("rel")), Ok(()));
+        assert_eq!(PathCheck::Readable.check(&file), Ok(()));
+        assert_eq!(PathCheck::Readable.check(&dir), Ok(()));
+        assert_eq!(PathCheck::Writable.check(&file), Ok(()));
+        assert_eq!(PathCheck::Writable.check(&dir), Ok(()));
+        assert_eq!(
+            PathCheck::MustExist.check(&missing),
+            Err(format!("'{}' does not exist", missing.display()))
+        );
+        assert!(PathCheck::MustBeFile.check(&dir).is_err());
+        assert!(PathCheck::MustBeDir.check(&file).is_err());
+        assert!(PathCheck::MustNotExist.check(&file).is_err());
+        assert!(PathCheck::ParentMustExist
+            .check(&missing.join("child"))
+            .is_err());
+        assert!(PathCheck::Readable.check(&missing).is_err());
+        assert_eq!(
+            PathCheck::Writable.check(&missing),
+            Err(format!("'{}' is not writable", missing.display()))
+        );
+        assert_eq!(fs::read_to_string(&file).unwrap(), "text"); // not truncated
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn path_check_writable_owner_only_directory() {
+        use std::os::unix::fs::{chown, MetadataExt, PermissionsExt};
+
+        let dir = temp_dir("writable");
+        let uid = fs::metadata(&dir).unwrap().uid();
+        // a directory with mode 0755 where only another owner can write: a temporary directory
+        // that is owned by `nobody` when the tests run as root, or the root directory
+        let other = if uid == 0 {
+            let other = dir.join("other");
+            fs::create_dir(&other).unwrap();
+            chown(&other, Some(65534), Some(65534)).unwrap();
+            other
+        } else {
+            PathBuf::from("/")
+        };
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        let metadata = fs::metadata(&other).unwrap();
+        assert_ne!(metadata.uid(), uid);
+        assert_eq!(metadata.mode() & 0o022, 0);
+
+        assert_eq!(PathCheck::Writable.check(&dir), Ok(()));
+        // root can write to any directory
+        assert_eq!(PathCheck::Writable.check(&other).is_ok(), uid == 0);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), usize::from(uid == 0)); // the probe file is removed
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn path_value_resolve() {
+        let dir = temp_dir("resolve");
+        fs::write(dir.join("in.txt"), "").unwrap();
+        let input = PathValue::new().cwd(&dir).check(PathCheck::MustBeFile);
+        let output = PathValue::new()
+            .expand_tilde()
+            .home(&dir)
+            .check(PathCheck::ParentMustExist)
+            .check(PathCheck::MustNotExist);
+
+        assert_eq!(input.resolve("./in.txt"), Ok(dir.join("in.txt")));
+        assert_eq!(
+            input.resolve("sub/../missing.txt"),
+            Err(format!(
+                "'{}' is not a file",
+                dir.join("missing.txt").display()
+            ))
+        );
+        assert_eq!(input.resolve(""), Err(String::from("empty path")));
+        assert_eq!(output.resolve("~/out.txt"), Ok(dir.join("out.txt")));
+        assert!(output.resolve("~/in.txt").is_err());
+        assert!(output.resolve("~/missing/out.txt").is_err());
+        assert!(PathValue::new().resolve("relative").unwrap().is_absolute());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,481 @@
+// Copyright 2018 Christopher Simpkins
+// Licensed under the MIT license
+
+//! Command line child process utilities
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use parsers::TokenKind;
+use Command;
+
+// The file extensions of executable files on Windows
+const EXECUTABLE_EXTENSIONS: [&str; 3] = [".exe", ".bat", ".cmd"];
+
+/// A builder for a `std::process::Command` that executes the trailing arguments of a command (e.g., `cargo build --release` in `mytool --fast -- cargo build --release`)
+///
+/// # Remarks
+/// The first trailing argument is the program and the remaining trailing arguments are the program arguments.  When a program override is defined with `TrailingCommand::program`, all of the trailing arguments are program arguments.
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::utilities::process::TrailingCommand;
+///
+/// let trailing = vec!["build".to_string(), "--release".to_string()];
+/// let child = TrailingCommand::new(&trailing)
+///     .program("cargo")
+///     .prefix_args(&["+nightly"])
+///     .env("CARGO_TERM_COLOR", "always")
+///     .build();
+///
+/// assert!(child.is_some());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrailingCommand {
+    args: Vec<String>,
+    program: Option<String>,
+    prefix_args: Vec<String>,
+    envs: Vec<(String, String)>,
+}
+
+impl TrailingCommand {
+    /// Instantiates and returns a new `TrailingCommand` struct for the trailing arguments in `args`
+    pub fn new(args: &[String]) -> Self {
+        TrailingCommand {
+            args: args.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    /// Instantiates and returns a new `TrailingCommand` struct for the arguments that follow the end of option parsing in `command` (`Command.remainder_argv`)
+    ///
+    /// # Remarks
+    /// These are the arguments that follow a double hyphen `--` idiom or, with `parsers::ParseOrder::RequireOrder` or a `spec::Spec::trailing_var_arg` positional, the arguments that begin at the first positional argument.
+    pub fn from_command(command: &Command) -> Self {
+        match &command.remainder_argv {
+            Some(x) => TrailingCommand::new(x),
+            None => TrailingCommand::new(&[]),
+        }
+    }
+
+    /// Returns the `TrailingCommand` with the program override `program`
+    pub fn program(mut self, program: &str) -> Self {
+        self.program = Some(program.to_string());
+        self
+    }
+
+    /// Returns the `TrailingCommand` with the arguments in `args` inserted before the trailing program arguments
+    pub fn prefix_args(mut self, args: &[&str]) -> Self {
+        self.prefix_args.extend(args.iter().map(|x| x.to_string()));
+        self
+    }
+
+    /// Returns the `TrailingCommand` with the environment variable `key` defined as `value` in the child process environment
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        self.envs.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Returns `Option<String>` with the program that is executed.
+    /// Returns `None` if there is no program override and there are no trailing arguments.
+    pub fn get_program(&self) -> Option<String> {
+        match &self.program {
+            Some(x) => Some(x.clone()),
+            None => self.args.first().cloned(),
+        }
+    }
+
+    /// Returns `Vec<String>` with the ordered program arguments, including prefix arguments.
+    pub fn get_args(&self) -> Vec<String> {
+        let trailing = match self.program {
+            Some(_) => &self.args[..],
+            None if self.args.is_empty() => &self.args[..],
+            None => &self.args[1..],
+        };
+        let mut args = self.prefix_args.clone();
+        args.extend_from_slice(trailing);
+        args
+    }
+
+    /// Returns `Option<std::process::Command>` that is ready to execute the program with its arguments and environment.
+    /// Returns `None` if there is no program override and there are no trailing arguments.
+    pub fn build(&self) -> Option<process::Command> {
+        let program = self.get_program()?;
+        let mut child = process::Command::new(program);
+        child.args(self.get_args());
+        for (key, value) in &self.envs {
+            child.env(key, value);
+        }
+        Some(child)
+    }
+}
+
+/// An external subcommand executable (e.g., `git-foo` for `git foo`) that is found in the search directories of an `ExternalResolver`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExternalCommand {
+    /// The subcommand name (e.g., `foo`)
+    pub name: String,
+    /// The path of the executable
+    pub path: PathBuf,
+    /// The ordered arguments that follow the subcommand in `Command.argv`
+    pub args: Vec<String>,
+}
+
+impl ExternalCommand {
+    /// Returns `std::process::Command` that is ready to execute the subcommand executable with its arguments
+    pub fn build(&self) -> process::Command {
+        let mut child = process::Command::new(&self.path);
+        child.args(&self.args);
+        child
+    }
+}
+
+/// A resolver for external subcommand executables that are named with the executable name, a hyphen, and the subcommand name (e.g., `cargo-foo` for `cargo foo`)
+///
+/// # Remarks
+/// The executables are searched for in the directories of the `PATH` environment variable by default, in order.  The first positional argument of a command is resolved as an external subcommand when it is not a known subcommand of the executable.
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::spec::Spec;
+/// use commandlines::utilities::process::ExternalResolver;
+///
+/// let resolver = ExternalResolver::new("tool").known(&["build", "test"]);
+/// let c = Spec::new("tool").parse(&["tool".to_string(), "build".to_string()]).unwrap();
+///
+/// assert_eq!(resolver.resolve(&c), None); // a known subcommand
+/// // if let Some(external) = resolver.resolve(&c) { external.build().status()?; }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExternalResolver {
+    prefix: String,
+    dirs: Vec<PathBuf>,
+    known: Vec<String>,
+}
+
+impl ExternalResolver {
+    /// Instantiates and returns a new `ExternalResolver` struct for the executable path or name `executable` (e.g., `git`) that searches the directories of the `PATH` environment variable
+    ///
+    /// # Remarks
+    /// The subcommand executables are named with the executable name (see `executable_name`) followed by a hyphen and the subcommand name (e.g., `git-foo`).
+    pub fn new(executable: &str) -> Self {
+        let name = executable_name(executable);
+        let dirs = match env::var_os("PATH") {
+            Some(x) => env::split_paths(&x).collect(),
+            None => Vec::new(),
+        };
+        ExternalResolver {
+            prefix: format!("{}-", name),
+            dirs,
+            known: Vec::new(),
+        }
+    }
+
+    /// Returns the `ExternalResolver` with the search directories in `dirs` in place of the `PATH` environment variable directories
+    pub fn dirs<P: AsRef<Path>>(mut self, dirs: &[P]) -> Self {
+        self.dirs = dirs.iter().map(|x| x.as_ref().to_path_buf()).collect();
+        self
+    }
+
+    /// Returns the `ExternalResolver` with the known subcommands in `names` that are not resolved as external subcommands
+    pub fn known(mut self, names: &[&str]) -> Self {
+        self.known.extend(names.iter().map(|x| x.to_string()));
+        self
+    }
+
+    /// Returns `Option<PathBuf>` with the path of the first executable for the subcommand `name` in the search directories.
+    /// Returns `None` if the executable is not found.
+    pub fn find(&self, name: &str) -> Option<PathBuf> {
+        if name.is_empty() || name.contains(['/', '\\']) {
+            return None;
+        }
+        let file_name = format!("{}{}", self.prefix, name);
+        self.dirs
+            .iter()
+            .flat_map(|dir| executable_candidates(&dir.join(&file_name)))
+            .find(|path| is_executable(path))
+    }
+
+    /// Returns `Option<ExternalCommand>` with the external subcommand for the first positional argument of `command` and the arguments that follow it.
+    /// Returns `None` if there is no positional argument before a double hyphen idiom, the argument is a known subcommand, or the executable is not found.
+    pub fn resolve(&self, command: &Command) -> Option<ExternalCommand> {
+        let token = command
+            .tokens
+            .iter()
+            .take_while(|x| x.kind != TokenKind::Terminator)
+            .find(|x| x.kind == TokenKind::Positional || x.kind == TokenKind::TrailingArg)?;
+        if self.known.contains(&token.text) {
+            return None;
+        }
+        let path = self.find(&token.text)?;
+        Some(ExternalCommand {
+            name: token.text.clone(),
+            path,
+            args: command.argv[token.index + 1..].to_vec(),
+        })
+    }
+
+    /// Returns `Vec<String>` with the sorted names of the external subcommands in the search directories (e.g., for help text)
+    pub fn list(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for dir in &self.dirs {
+            let entries = match fs::read_dir(dir) {
+                Ok(x) => x,
+                Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                let file_name = entry.file_name().to_string_lossy().into_owned();
+                let name = match file_name.strip_prefix(&self.prefix) {
+                    Some(x) if is_executable(&entry.path()) => x,
+                    _ => continue,
+                };
+                let name = subcommand_name(name);
+                if !name.is_empty() && !self.known.iter().any(|x| x == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+/// Returns `&str` with the file name of the executable path or name `executable` without an executable file extension (`.exe`, `.bat`, or `.cmd`)
+///
+/// # Remarks
+/// Both `/` and `\` are path separators.  Other file extensions are part of the executable name (e.g., `my.tool` for `/usr/bin/my.tool`).
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::utilities::process::executable_name;
+///
+/// assert_eq!(executable_name("/usr/bin/my.tool"), "my.tool");
+/// assert_eq!(executable_name("C:\\bin\\tool.EXE"), "tool");
+/// ```
+pub fn executable_name(executable: &str) -> &str {
+    let file_name = executable.rsplit(['/', '\\']).next().unwrap_or(executable);
+    strip_executable_extension(file_name).unwrap_or(file_name)
+}
+
+// Returns the file name `name` without an executable file extension, or `None` if it does not have one
+fn strip_executable_extension(name: &str) -> Option<&str> {
+    EXECUTABLE_EXTENSIONS.iter().find_map(|x| {
+        let end = name.len().checked_sub(x.len()).filter(|&i| i > 0)?;
+        let extension = name.get(end..)?;
+        match extension.eq_ignore_ascii_case(x) {
+            true => Some(&name[..end]),
+            false => None,
+        }
+    })
+}
+
+// Returns the executable file paths for `path`
+#[cfg(windows)]
+fn executable_candidates(path: &Path) -> Vec<PathBuf> {
+    EXECUTABLE_EXTENSIONS
+        .iter()
+        .map(|x| PathBuf::from(format!("{}{}", path.display(), x)))
+        .collect()
+}
+
+// Returns the executable file paths for `path`
+#[cfg(not(windows))]
+fn executable_candidates(path: &Path) -> Vec<PathBuf> {
+    vec![path.to_path_buf()]
+}
+
+// Returns the subcommand name without an executable file extension, or an empty string for a file
+// that is not executable
+#[cfg(windows)]
+fn subcommand_name(name: &str) -> &str {
+    strip_executable_extension(name).unwrap_or("")
+}
+
+// Returns the subcommand name without an executable file extension
+#[cfg(not(windows))]
+fn subcommand_name(name: &str) -> &str {
+    name
+}
+
+// Returns boolean for the question "Is the file at `path` an executable file?"
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path).is_ok_and(|x| x.is_file() && x.permissions().mode() & 0o111 != 0)
+}
+
+// Returns boolean for the question "Is the file at `path` an executable file?"
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argv;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn trailing_command_program_and_args() {
+        let tc = TrailingCommand::new(&argv(&["cargo", "build", "--release"]));
+        assert_eq!(tc.get_program(), Some("cargo".to_string()));
+        assert_eq!(tc.get_args(), argv(&["build", "--release"]));
+
+        let child = tc.build().unwrap();
+        assert_eq!(child.get_program(), OsStr::new("cargo"));
+        let args: Vec<&OsStr> = child.get_args().collect();
+        assert_eq!(args, vec![OsStr::new("build"), OsStr::new("--release")]);
+    }
+
+    #[test]
+    fn trailing_command_program_override_prefix_and_env() {
+        let child = TrailingCommand::new(&argv(&["build", "--release"]))
+            .program("cargo")
+            .prefix_args(&["+nightly"])
+            .env("RUST_LOG", "debug")
+            .build()
+            .unwrap();
+
+        assert_eq!(child.get_program(), OsStr::new("cargo"));
+        let args: Vec<&OsStr> = child.get_args().collect();
+        assert_eq!(
+            args,
+            vec![
+                OsStr::new("+nightly"),
+                OsStr::new("build"),
+                OsStr::new("--release")
+            ]
+        );
+        let envs: Vec<(&OsStr, Option<&OsStr>)> = child.get_envs().collect();
+        assert_eq!(
+            envs,
+            vec![(OsStr::new("RUST_LOG"), Some(OsStr::new("debug")))]
+        );
+    }
+
+    #[test]
+    fn trailing_command_without_program() {
+        let tc = TrailingCommand::new(&[]);
+        assert_eq!(tc.get_program(), None);
+        assert_eq!(tc.get_args(), Vec::<String>::new());
+        assert!(tc.build().is_none());
+        assert!(TrailingCommand::new(&[]).program("ls").build().is_some());
+    }
+
+    #[test]
+    fn trailing_command_from_command() {
+        let c = Command::new_with_vec(argv(&[
+            "mytool",
+            "--fast",
+            "--",
+            "cargo",
+            "build",
+            "--release",
+        ]));
+        let tc = TrailingCommand::from_command(&c);
+        assert_eq!(tc.get_program(), Some("cargo".to_string()));
+        assert_eq!(tc.get_args(), argv(&["build", "--release"]));
+
+        let c = Command::new_with_vec(argv(&["mytool", "--fast"]));
+        assert_eq!(TrailingCommand::from_command(&c).get_program(), None);
+    }
+
+    // Returns a new temporary directory with the executable and non-executable files in `files`
+    #[cfg(unix)]
+    fn temp_dir(name: &str, files: &[(&str, bool)]) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = env::temp_dir().join(format!(
+            "commandlines-process-{}-{}",
+            std::process::id(),
+            name
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        for (file, executable) in files {
+            let path = dir.join(file);
+            fs::write(&path, "#!/bin/sh\n").unwrap();
+            let mode = if *executable { 0o755 } else { 0o644 };
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn function_executable_name() {
+        assert_eq!(executable_name("tool"), "tool");
+        assert_eq!(executable_name("/usr/bin/my.tool"), "my.tool");
+        assert_eq!(executable_name("C:\\bin\\tool.exe"), "tool");
+        assert_eq!(executable_name("bin/tool.Cmd"), "tool");
+        assert_eq!(executable_name("tool.bat.sh"), "tool.bat.sh");
+        assert_eq!(executable_name(".exe"), ".exe");
+        assert_eq!(executable_name("/usr/bin/"), "");
+        assert_eq!(ExternalResolver::new("/usr/bin/my.tool").prefix, "my.tool-");
+        assert_eq!(ExternalResolver::new("git.exe").prefix, "git-");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn external_resolver_find_and_list() {
+        let dir1 = temp_dir("dir1", &[("tool-foo", true), ("tool-data", false)]);
+        let dir2 = temp_dir(
+            "dir2",
+            &[("tool-foo", true), ("tool-bar", true), ("other-baz", true)],
+        );
+        let resolver = ExternalResolver::new("/usr/bin/tool")
+            .dirs(&[&dir1, &dir2, &dir1.join("missing")])
+            .known(&["bar"]);
+
+        assert_eq!(resolver.find("foo"), Some(dir1.join("tool-foo")));
+        assert_eq!(resolver.find("bar"), Some(dir2.join("tool-bar")));
+        assert_eq!(resolver.find("data"), None);
+        assert_eq!(resolver.find("baz"), None);
+        assert_eq!(resolver.find("../dir2/tool-foo"), None);
+        assert_eq!(resolver.list(), argv(&["foo"]));
+
+        fs::remove_dir_all(&dir1).unwrap();
+        fs::remove_dir_all(&dir2).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn external_resolver_resolve() {
+        let dir = temp_dir("resolve", &[("tool-foo", true), ("tool-build", true)]);
+        let resolver = ExternalResolver::new("tool")
+            .dirs(&[&dir])
+            .known(&["build"]);
+        let c1 = Command::new_with_vec(argv(&["tool", "-v", "foo", "--x", "arg"]));
+        let c2 = Command::new_with_vec(argv(&["tool", "build"]));
+        let c3 = Command::new_with_vec(argv(&["tool", "--", "foo"]));
+        let c4 = Command::new_with_vec(argv(&["tool", "missing", "foo"]));
+
+        let external = resolver.resolve(&c1).unwrap();
+        assert_eq!(
+            external,
+            ExternalCommand {
+                name: "foo".to_string(),
+                path: dir.join("tool-foo"),
+                args: argv(&["--x", "arg"]),
+            }
+        );
+        let child = external.build();
+        assert_eq!(child.get_program(), dir.join("tool-foo").as_os_str());
+        assert_eq!(
+            child.get_args().collect::<Vec<&OsStr>>(),
+            vec![OsStr::new("--x"), OsStr::new("arg")]
+        );
+        assert_eq!(resolver.resolve(&c2), None);
+        assert_eq!(resolver.resolve(&c3), None);
+        assert_eq!(resolver.resolve(&c4), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright 2018 Christopher Simpkins
+// Licensed under the MIT license
+
+//! File or standard stream command line argument utilities
+//!
+//! Per POSIX guidelines, a single hyphen `-` argument represents the standard input stream for an input argument and the standard output stream for an output argument.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// An input argument that is the standard input stream `-` or a file path
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::utilities::stdio::InputSource;
+///
+/// let sources: Vec<InputSource> = vec!["-", "in.txt"].into_iter().map(InputSource::new).collect();
+///
+/// assert!(sources[0].is_stdin());
+/// assert_eq!(sources[1].to_string(), "in.txt");
+/// // let readers = InputSource::open_all(&sources)?;
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum InputSource {
+    /// The standard input stream
+    Stdin,
+    /// A file path
+    File(PathBuf),
+}
+
+impl InputSource {
+    /// Instantiates and returns a new `InputSource` for the command line argument `arg`
+    pub fn new(arg: &str) -> Self {
+        match arg {
+            "-" => InputSource::Stdin,
+            _ => InputSource::File(PathBuf::from(arg)),
+        }
+    }
+
+    /// Returns boolean for the question "Is the input the standard input stream?"
+    pub fn is_stdin(&self) -> bool {
+        *self == InputSource::Stdin
+    }
+
+    /// Returns `Option<&Path>` with the file path.
+    /// Returns `None` if the input is the standard input stream.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            InputSource::Stdin => None,
+            InputSource::File(x) => Some(x),
+        }
+    }
+
+    /// Returns `io::Result<Box<dyn Read>>` with a reader for the standard input stream or the opened file
+    pub fn open(&self) -> io::Result<Box<dyn Read>> {
+        match self {
+            InputSource::Stdin => Ok(Box::new(io::stdin())),
+            InputSource::File(x) => Ok(Box::new(fs::File::open(x)?)),
+        }
+    }
+
+    /// Returns `io::Result<Vec<Box<dyn Read>>>` with a reader for each input in `sources`
+    ///
+    /// # Remarks
+    /// Returns an `io::ErrorKind::InvalidInput` error before any input is opened if the standard input stream is requested more than once, because it can only be read once.
+    pub fn open_all(sources: &[InputSource]) -> io::Result<Vec<Box<dyn Read>>> {
+        check_single_stdin(sources).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        sources.iter().map(|x| x.open()).collect()
+    }
+}
+
+// Display trait
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputSource::Stdin => write!(f, "-"),
+            InputSource::File(x) => write!(f, "{}", x.display()),
+        }
+    }
+}
+
+// FromStr trait
+impl FromStr for InputSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "" => Err(String::from("empty path")),
+            _ => Ok(InputSource::new(s)),
+        }
+    }
+}
+
+/// An output argument that is the standard output stream `-` or a file path
+///
+/// # Examples
+///
+/// ```
+/// use commandlines::utilities::stdio::OutputTarget;
+///
+/// let target = OutputTarget::new("-");
+///
+/// assert!(target.is_stdout());
+/// // let mut writer = target.open()?;
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OutputTarget {
+    /// The standard output stream
+    Stdout,
+    /// A file path
+    File(PathBuf),
+}
+
+impl OutputTarget {
+    /// Instantiates and returns a new `OutputTarget` for the command line argument `arg`
+    pub fn new(arg: &str) -> Self {
+        match arg {
+            "-" => OutputTarget::Stdout,
+            _ => OutputTarget::File(PathBuf::from(arg)),
+        }
+    }
+
+    /// Returns boolean for the question "Is the output the standard output stream?"
+    pub fn is_stdout(&self) -> bool {
+        *self == OutputTarget::Stdout
+    }
+
+    /// Returns `Option<&Path>` with the file path.
+    /// Returns `None` if the output is the standard output stream.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            OutputTarget::Stdout => None,
+            OutputTarget::File(x) => Some(x),
+        }
+    }
+
+    /// Returns `io::Result<Box<dyn Write>>` with a writer for the standard output stream or the created file.  An existing file is truncated.
+    pub fn open(&self) -> io::Result<Box<dyn Write>> {
+        match self {
+            OutputTarget::Stdout => Ok(Box::new(io::stdout())),
+            OutputTarget::File(x) => Ok(Box::new(fs::File::create(x)?)),
+        }
+    }
+}
+
+// Display trait
+impl fmt::Display for OutputTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OutputTarget::Stdout => write!(f, "-"),
+            OutputTarget::File(x) => write!(f, "{}", x.display()),
+        }
+    }
+}
+
+// FromStr trait
+impl FromStr for OutputTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "" => Err(String::from("empty path")),
+            _ => Ok(OutputTarget::new(s)),
+        }
+    }
+}
+
+/// Returns `Result<(), String>` with a failure message if the standard input stream is requested more than once in `sources`
+pub fn check_single_stdin(sources: &[InputSource]) -> Result<(), String> {
+    if sources.iter().filter(|x| x.is_stdin()).count() > 1 {
+        Err(String::from(
+            "standard input '-' is requested more than once",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn input_source_new_and_display() {
+        let stdin = InputSource::new("-");
+        let file = InputSource::new("dir/in.txt");
+
+        assert!(stdin.is_stdin());
+        assert!(!file.is_stdin());
+        assert_eq!(stdin.path(), None);
+        assert_eq!(file.path(), Some(Path::new("dir/in.txt")));
+        assert_eq!(stdin.to_string(), "-");
+        assert_eq!(file.to_string(), "dir/in.txt");
+        assert_eq!("--".parse::<InputSource>(), Ok(InputSource::new("--")));
+        assert!("".parse::<InputSource>().is_err());
+    }
+
+    #[test]
+    fn input_source_open() {
+        let path = env::temp_dir().join(format!("commandlines-stdio-{}-in", std::process::id()));
+        fs::write(&path, "contents").unwrap();
+
+        let mut text = String::new();
+        InputSource::File(path.clone())
+            .open()
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "contents");
+        assert_eq!(
+            InputSource::open_all(&[InputSource::Stdin, InputSource::File(path.clone())])
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let missing = InputSource::File(path.with_extension("missing")).open();
+        assert_eq!(missing.err().unwrap().kind(), io::ErrorKind::NotFound);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn input_source_stdin_requested_more_than_once() {
+        let sources = vec![
+            InputSource::Stdin,
+            InputSource::new("a.txt"),
+            InputSource::Stdin,
+        ];
+        let error = InputSource::open_all(&sources).err().unwrap();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            error.to_string(),
+            "standard input '-' is requested more than once"
+        );
+        assert!(check_single_stdin(&sources[..2]).is_ok());
+    }
+
+    #[test]
+    fn output_target_open() {
+        let path = env::temp_dir().join(format!("commandlines-stdio-{}-out", std::process::id()));
+        let target: OutputTarget = path.to_str().unwrap().parse().unwrap();
+
+        assert!(!target.is_stdout());
+        assert!(OutputTarget::new("-").is_stdout());
+        assert_eq!(OutputTarget::new("-").path(), None);
+        assert_eq!(OutputTarget::new("-").to_string(), "-");
+        target.open().unwrap().write_all(b"written").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "written");
+
+        fs::remove_file(&path).unwrap();
+    }
+}